@@ -1,5 +1,58 @@
+/// Sentinel order id asking Open to assign the next id from a counter lane of the authority.
+pub const AUTO_ID: u64 = u64::MAX;
+
+/// The first id assigned by a counter.
+/// Assigned ids count up from the top half of the range. Ids chosen by callers must be below it.
+pub const AUTO_ID_START: u64 = 1 << 63;
+
+/// Time in seconds after expiry during which only the authority may close a settled order.
+pub const CLOSE_GRACE_PERIOD: i64 = 60 * 60 * 24 * 7;
+
+/// Seed of the config account PDA.
+pub const CONFIG: &[u8] = b"config";
+
+/// Seed of the counter account PDA.
+pub const COUNTER: &[u8] = b"counter";
+
+/// Denominator of fees expressed in basis points.
+pub const DENOMINATOR_BPS: u64 = 10_000;
+
+/// The number of ids in the range of each counter lane.
+/// The 2^16 lanes split the assigned ids between them.
+pub const LANE_SIZE: u64 = 1 << 47;
+
+/// Seed of the maker account PDA.
+pub const MAKER: &[u8] = b"maker";
+
+/// Seed of the market account PDA.
+pub const MARKET: &[u8] = b"market";
+
+/// The maximum protocol fee, in basis points.
+pub const MAX_FEE: u64 = 1_000;
+
+/// The maximum referral fee a taker may pay on a fill, in basis points.
+pub const MAX_REFERRAL_FEE: u64 = 500;
+
 /// Seed of the order account PDA.
 pub const ORDER: &[u8] = b"order";
 
+/// Order flag for fills that swap token A to the taker immediately.
+pub const ORDER_FLAG_ATOMIC: u32 = 1 << 1;
+
+/// Order flag for partial fills that settle pro rata at expiry.
+pub const ORDER_FLAG_PRO_RATA: u32 = 1 << 0;
+
+/// Order flag for fills that mint transferable receipt tokens.
+pub const ORDER_FLAG_TOKENIZED: u32 = 1 << 2;
+
+/// The current version of the order account layout.
+pub const ORDER_VERSION: u8 = 2;
+
 /// Seed of the receipt account PDA.
 pub const RECEIPT: &[u8] = b"receipt";
+
+/// Seed of the receipt mint PDA.
+pub const RECEIPT_MINT: &[u8] = b"receipt_mint";
+
+/// Seed of the temporary token account used to unwrap native SOL.
+pub const UNWRAP: &[u8] = b"unwrap";
@@ -0,0 +1,62 @@
+use steel::*;
+
+#[derive(Debug, Error, Clone, Copy, PartialEq, Eq, IntoPrimitive)]
+#[repr(u32)]
+pub enum ProtobookError {
+    #[error("The order has expired")]
+    OrderExpired = 0,
+    #[error("The order has not expired")]
+    OrderNotExpired = 1,
+    #[error("The order is filled")]
+    OrderFilled = 2,
+    #[error("The order has already been collected")]
+    AlreadyCollected = 3,
+    #[error("The receipt does not belong to the order")]
+    ReceiptOrderMismatch = 4,
+    #[error("Tokens A and B must be different mints")]
+    SameMint = 5,
+    #[error("The order has not started")]
+    OrderNotStarted = 6,
+    #[error("The order has not been collected")]
+    NotCollected = 7,
+    #[error("The order has unredeemed receipts")]
+    UnredeemedReceipts = 8,
+    #[error("The order has been cancelled")]
+    OrderCancelled = 9,
+    #[error("The signer may not fill the order")]
+    TakerNotAllowed = 10,
+    #[error("The fill is below the minimum or not a multiple of the lot size")]
+    InvalidFill = 11,
+    #[error("The program is paused")]
+    ProgramPaused = 12,
+    #[error("The fee exceeds the maximum")]
+    FeeTooHigh = 13,
+    #[error("Tokenized orders cannot be replaced")]
+    OrderTokenized = 14,
+    #[error("The order does not accept a tip")]
+    TipNotAllowed = 15,
+    #[error("The terms can only change in favor of takers once they have deposited")]
+    UnfavorableAmendment = 16,
+    #[error("There is nothing to redeem")]
+    NothingToRedeem = 17,
+    #[error("The signer is not the authority")]
+    NotAuthority = 18,
+    #[error("The order terms are invalid")]
+    InvalidTerms = 19,
+    #[error("The mint does not match the order")]
+    MintMismatch = 20,
+    #[error("The referrer account is invalid")]
+    InvalidReferrer = 21,
+    #[error("Flags must be zero or one")]
+    InvalidFlag = 22,
+    #[error("The fee collector does not match the config")]
+    FeeCollectorMismatch = 23,
+    #[error("The receipt cannot be transferred to its own authority")]
+    SameAuthority = 24,
+    #[error("The counter lane has assigned all of its ids")]
+    LaneExhausted = 25,
+    #[error("The order is not on the market's price grid")]
+    OffMarketGrid = 26,
+}
+
+error!(ProtobookError);
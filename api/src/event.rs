@@ -0,0 +1,454 @@
+use std::str::FromStr;
+
+use base64::{prelude::BASE64_STANDARD, Engine};
+use steel::*;
+
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, IntoPrimitive, TryFromPrimitive)]
+pub enum ProtobookEvent {
+    OrderOpened = 0,
+    OrderFilled = 1,
+    OrderAmended = 2,
+    OrderCancelled = 3,
+    OrdersCancelled = 4,
+    OrderCollected = 5,
+    OrderReplaced = 6,
+    ReceiptRedeemed = 7,
+    ReceiptTransferred = 8,
+    OrderClosed = 9,
+    ConfigUpdated = 10,
+    OrderMigrated = 11,
+    ReceiptMigrated = 12,
+    MarketInitialized = 13,
+}
+
+/// Emitted when an order is opened.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+pub struct OrderOpened {
+    pub authority: Pubkey,
+    pub order: Pubkey,
+    pub mint_a: Pubkey,
+    pub mint_b: Pubkey,
+    pub amount_a: u64,
+    pub amount_b: u64,
+    pub expires_at: i64,
+    pub id: u64,
+}
+
+/// Emitted when an order is filled.
+/// The amount is the deposit received in escrow, net of any referral and transfer fee.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+pub struct OrderFilled {
+    pub order: Pubkey,
+    pub taker: Pubkey,
+    pub amount: u64,
+    /// The amount of token A swapped to the taker, net of the protocol fee, if the order is atomic.
+    pub amount_a: u64,
+    pub fee: u64,
+    pub referral: u64,
+    pub total_deposits: u64,
+}
+
+/// Emitted when the terms of an order are amended.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+pub struct OrderAmended {
+    pub order: Pubkey,
+    pub amount_a: u64,
+    pub amount_b: u64,
+    pub expires_at: i64,
+}
+
+/// Emitted when an order is cancelled.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+pub struct OrderCancelled {
+    pub order: Pubkey,
+    pub authority: Pubkey,
+}
+
+/// Emitted when all open orders of an authority are cancelled.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+pub struct OrdersCancelled {
+    pub authority: Pubkey,
+    pub nonce: u64,
+}
+
+/// Emitted when an order is collected.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+pub struct OrderCollected {
+    pub order: Pubkey,
+    pub owner: Pubkey,
+    pub amount_a: u64,
+    /// The amount of token B paid to the owner, net of the protocol fee.
+    pub amount_b: u64,
+    pub fee: u64,
+    pub tip: u64,
+}
+
+/// Emitted when an order is replaced by a new one.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+pub struct OrderReplaced {
+    pub order: Pubkey,
+    pub new_order: Pubkey,
+    /// The amount of token B paid to the authority, net of the protocol fee.
+    pub amount_b: u64,
+    pub fee: u64,
+}
+
+/// Emitted when a receipt is redeemed.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+pub struct ReceiptRedeemed {
+    pub order: Pubkey,
+    pub authority: Pubkey,
+    pub owner: Pubkey,
+    pub mint: Pubkey,
+    pub deposit: u64,
+    /// The amount paid to the owner, net of the protocol fee.
+    pub amount: u64,
+    pub fee: u64,
+    pub tip: u64,
+}
+
+/// Emitted when a receipt is transferred to a new authority.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+pub struct ReceiptTransferred {
+    pub order: Pubkey,
+    pub authority: Pubkey,
+    pub new_authority: Pubkey,
+    pub deposit: u64,
+}
+
+/// Emitted when an order account is closed.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+pub struct OrderClosed {
+    pub order: Pubkey,
+    pub authority: Pubkey,
+}
+
+/// Emitted when the config is initialized or updated.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+pub struct ConfigUpdated {
+    pub admin: Pubkey,
+    pub fee: u64,
+    pub fee_collector: Pubkey,
+    pub is_paused: u64,
+}
+
+/// Emitted when an order account is migrated to the current layout.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+pub struct OrderMigrated {
+    pub order: Pubkey,
+    pub authority: Pubkey,
+}
+
+/// Emitted when a receipt account is migrated to the current layout.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+pub struct ReceiptMigrated {
+    pub receipt: Pubkey,
+    pub authority: Pubkey,
+}
+
+/// Emitted when a market is initialized.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+pub struct MarketInitialized {
+    pub market: Pubkey,
+    pub mint_a: Pubkey,
+    pub mint_b: Pubkey,
+    pub lot_size: u64,
+    pub min_order_size: u64,
+    pub tick_size: u64,
+}
+
+/// Implements logging for an event, prefixed with its discriminator so it can be decoded.
+macro_rules! protobook_event {
+    ($struct_name:ident) => {
+        impl Discriminator for $struct_name {
+            fn discriminator() -> u8 {
+                ProtobookEvent::$struct_name.into()
+            }
+        }
+
+        impl $struct_name {
+            pub fn to_bytes(&self) -> Vec<u8> {
+                [&[Self::discriminator()], bytemuck::bytes_of(self)].concat()
+            }
+
+            pub fn log(&self) {
+                solana_program::log::sol_log_data(&[&self.to_bytes()]);
+            }
+        }
+    };
+}
+
+protobook_event!(OrderOpened);
+protobook_event!(OrderFilled);
+protobook_event!(OrderAmended);
+protobook_event!(OrderCancelled);
+protobook_event!(OrdersCancelled);
+protobook_event!(OrderCollected);
+protobook_event!(OrderReplaced);
+protobook_event!(ReceiptRedeemed);
+protobook_event!(ReceiptTransferred);
+protobook_event!(OrderClosed);
+protobook_event!(ConfigUpdated);
+protobook_event!(OrderMigrated);
+protobook_event!(ReceiptMigrated);
+protobook_event!(MarketInitialized);
+
+/// A decoded program event.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Event {
+    OrderOpened(OrderOpened),
+    OrderFilled(OrderFilled),
+    OrderAmended(OrderAmended),
+    OrderCancelled(OrderCancelled),
+    OrdersCancelled(OrdersCancelled),
+    OrderCollected(OrderCollected),
+    OrderReplaced(OrderReplaced),
+    ReceiptRedeemed(ReceiptRedeemed),
+    ReceiptTransferred(ReceiptTransferred),
+    OrderClosed(OrderClosed),
+    ConfigUpdated(ConfigUpdated),
+    OrderMigrated(OrderMigrated),
+    ReceiptMigrated(ReceiptMigrated),
+    MarketInitialized(MarketInitialized),
+}
+
+impl Event {
+    /// Decodes an event from the data logged by the program.
+    pub fn from_bytes(data: &[u8]) -> Option<Self> {
+        let (discriminator, data) = data.split_first()?;
+        let event = match ProtobookEvent::try_from(*discriminator).ok()? {
+            ProtobookEvent::OrderOpened => {
+                Self::OrderOpened(bytemuck::try_pod_read_unaligned(data).ok()?)
+            }
+            ProtobookEvent::OrderFilled => {
+                Self::OrderFilled(bytemuck::try_pod_read_unaligned(data).ok()?)
+            }
+            ProtobookEvent::OrderAmended => {
+                Self::OrderAmended(bytemuck::try_pod_read_unaligned(data).ok()?)
+            }
+            ProtobookEvent::OrderCancelled => {
+                Self::OrderCancelled(bytemuck::try_pod_read_unaligned(data).ok()?)
+            }
+            ProtobookEvent::OrdersCancelled => {
+                Self::OrdersCancelled(bytemuck::try_pod_read_unaligned(data).ok()?)
+            }
+            ProtobookEvent::OrderCollected => {
+                Self::OrderCollected(bytemuck::try_pod_read_unaligned(data).ok()?)
+            }
+            ProtobookEvent::OrderReplaced => {
+                Self::OrderReplaced(bytemuck::try_pod_read_unaligned(data).ok()?)
+            }
+            ProtobookEvent::ReceiptRedeemed => {
+                Self::ReceiptRedeemed(bytemuck::try_pod_read_unaligned(data).ok()?)
+            }
+            ProtobookEvent::ReceiptTransferred => {
+                Self::ReceiptTransferred(bytemuck::try_pod_read_unaligned(data).ok()?)
+            }
+            ProtobookEvent::OrderClosed => {
+                Self::OrderClosed(bytemuck::try_pod_read_unaligned(data).ok()?)
+            }
+            ProtobookEvent::ConfigUpdated => {
+                Self::ConfigUpdated(bytemuck::try_pod_read_unaligned(data).ok()?)
+            }
+            ProtobookEvent::OrderMigrated => {
+                Self::OrderMigrated(bytemuck::try_pod_read_unaligned(data).ok()?)
+            }
+            ProtobookEvent::ReceiptMigrated => {
+                Self::ReceiptMigrated(bytemuck::try_pod_read_unaligned(data).ok()?)
+            }
+            ProtobookEvent::MarketInitialized => {
+                Self::MarketInitialized(bytemuck::try_pod_read_unaligned(data).ok()?)
+            }
+        };
+        Some(event)
+    }
+
+    /// Decodes the events in the log messages of a transaction.
+    /// Logs of other programs are skipped, as are any data lines that are not Protobook events.
+    /// Only runtime log lines change the program frame, so programs cannot spoof them with `sol_log`.
+    pub fn from_logs(logs: &[String]) -> Vec<Self> {
+        let mut events = vec![];
+        let mut stack = vec![];
+        for log in logs {
+            if let Some(data) = log.strip_prefix("Program data: ") {
+                if stack.last() != Some(&crate::ID) {
+                    continue;
+                }
+                let Ok(data) = BASE64_STANDARD.decode(data) else {
+                    continue;
+                };
+                if let Some(event) = Self::from_bytes(&data) {
+                    events.push(event);
+                }
+            } else {
+                match Frame::from_log(log) {
+                    Some(Frame::Invoke(program_id)) => stack.push(program_id),
+                    Some(Frame::Exit(program_id)) => {
+                        if stack.last() == Some(&program_id) {
+                            stack.pop();
+                        }
+                    }
+                    None => {}
+                }
+            }
+        }
+        events
+    }
+}
+
+/// A log line written by the runtime when a program is entered or exited.
+enum Frame {
+    Invoke(Pubkey),
+    Exit(Pubkey),
+}
+
+impl Frame {
+    /// Parses `Program <id> invoke [<depth>]`, `Program <id> success`, or `Program <id> failed: <error>`.
+    fn from_log(log: &str) -> Option<Self> {
+        let (program_id, rest) = log.strip_prefix("Program ")?.split_once(' ')?;
+        let program_id = Pubkey::from_str(program_id).ok()?;
+        if let Some(depth) = rest
+            .strip_prefix("invoke [")
+            .and_then(|r| r.strip_suffix(']'))
+        {
+            depth.parse::<usize>().ok()?;
+            Some(Self::Invoke(program_id))
+        } else if rest == "success" || rest.starts_with("failed: ") {
+            Some(Self::Exit(program_id))
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn data(event: &[u8]) -> String {
+        format!("Program data: {}", BASE64_STANDARD.encode(event))
+    }
+
+    fn cancelled(order: Pubkey) -> OrderCancelled {
+        OrderCancelled {
+            order,
+            authority: Pubkey::new_unique(),
+        }
+    }
+
+    #[test]
+    fn test_from_logs_skips_nested_cpi_data() {
+        let program = crate::ID.to_string();
+        let token = spl_token::ID.to_string();
+        let foreign = cancelled(Pubkey::new_unique());
+        let closed = OrderClosed {
+            order: Pubkey::new_unique(),
+            authority: Pubkey::new_unique(),
+        };
+        let logs = vec![
+            format!("Program {program} invoke [1]"),
+            "Program log: Instruction: Close".to_string(),
+            format!("Program {token} invoke [2]"),
+            data(&foreign.to_bytes()),
+            format!("Program {token} consumed 2000 of 190000 compute units"),
+            format!("Program {token} success"),
+            data(&closed.to_bytes()),
+            format!("Program {program} consumed 20000 of 200000 compute units"),
+            format!("Program {program} success"),
+        ];
+        assert_eq!(Event::from_logs(&logs), vec![Event::OrderClosed(closed)]);
+    }
+
+    #[test]
+    fn test_from_logs_reads_program_invoked_by_cpi() {
+        let program = crate::ID.to_string();
+        let router = Pubkey::new_unique().to_string();
+        let inner = cancelled(Pubkey::new_unique());
+        let outer = cancelled(Pubkey::new_unique());
+        let logs = vec![
+            format!("Program {router} invoke [1]"),
+            data(&outer.to_bytes()),
+            format!("Program {program} invoke [2]"),
+            data(&inner.to_bytes()),
+            format!("Program {program} success"),
+            data(&outer.to_bytes()),
+            format!("Program {router} success"),
+            format!("Program {program} invoke [1]"),
+            data(&outer.to_bytes()),
+            format!("Program {program} success"),
+        ];
+        assert_eq!(
+            Event::from_logs(&logs),
+            vec![Event::OrderCancelled(inner), Event::OrderCancelled(outer)]
+        );
+    }
+
+    #[test]
+    fn test_from_logs_pops_failed_cpi() {
+        let program = crate::ID.to_string();
+        let token = spl_token::ID.to_string();
+        let event = cancelled(Pubkey::new_unique());
+        let logs = vec![
+            format!("Program {program} invoke [1]"),
+            format!("Program {token} invoke [2]"),
+            format!("Program {token} failed: insufficient funds"),
+            data(&event.to_bytes()),
+            format!("Program {program} success"),
+        ];
+        assert_eq!(Event::from_logs(&logs), vec![Event::OrderCancelled(event)]);
+    }
+
+    #[test]
+    fn test_from_logs_skips_invalid_data() {
+        let program = crate::ID.to_string();
+        let event = cancelled(Pubkey::new_unique());
+        let truncated = &event.to_bytes()[..40];
+        let logs = vec![
+            format!("Program {program} invoke [1]"),
+            "Program data: not base64!".to_string(),
+            data(truncated),
+            data(&[u8::MAX]),
+            data(&event.to_bytes()),
+            format!("Program {program} success"),
+        ];
+        assert_eq!(Event::from_logs(&logs), vec![Event::OrderCancelled(event)]);
+    }
+
+    #[test]
+    fn test_from_logs_ignores_spoofed_frames() {
+        // A program invoked by Protobook logs lines that mimic the runtime to pass off its data.
+        let program = crate::ID.to_string();
+        let attacker = Pubkey::new_unique().to_string();
+        let spoofed = cancelled(Pubkey::new_unique());
+        let event = cancelled(Pubkey::new_unique());
+        let logs = vec![
+            format!("Program {program} invoke [1]"),
+            format!("Program {attacker} invoke [2]"),
+            "Program log: success".to_string(),
+            format!("Program log: Program {attacker} success"),
+            format!("Program log: Program {program} invoke [3]"),
+            format!("Program {attacker} invoke"),
+            format!("Program {attacker} success!"),
+            data(&spoofed.to_bytes()),
+            format!("Program {attacker} success"),
+            data(&event.to_bytes()),
+            format!("Program {program} success"),
+        ];
+        assert_eq!(Event::from_logs(&logs), vec![Event::OrderCancelled(event)]);
+    }
+}
@@ -9,12 +9,32 @@ pub enum ProtobookInstruction {
     Fill = 3,
     Open = 4,
     Redeem = 5,
+    Amend = 6,
+    Replace = 7,
+    CancelAll = 8,
+    TransferReceipt = 9,
+    InitializeConfig = 10,
+    UpdateConfig = 11,
+    Migrate = 12,
+    InitMarket = 13,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod, Zeroable)]
+pub struct Amend {
+    pub amount_a: [u8; 8],
+    pub amount_b: [u8; 8],
+    pub expires_at: [u8; 8],
 }
 
 #[repr(C)]
 #[derive(Clone, Copy, Debug, Pod, Zeroable)]
 pub struct Cancel {}
 
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod, Zeroable)]
+pub struct CancelAll {}
+
 #[repr(C)]
 #[derive(Clone, Copy, Debug, Pod, Zeroable)]
 pub struct Close {}
@@ -27,30 +47,92 @@ pub struct Collect {}
 #[derive(Clone, Copy, Debug, Pod, Zeroable)]
 pub struct Expire {}
 
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod, Zeroable)]
+pub struct InitMarket {
+    pub lot_size: [u8; 8],
+    pub min_order_size: [u8; 8],
+    pub tick_size: [u8; 8],
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod, Zeroable)]
+pub struct InitializeConfig {
+    pub fee: [u8; 8],
+    pub fee_collector: Pubkey,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod, Zeroable)]
+pub struct Migrate {}
+
 #[repr(C)]
 #[derive(Clone, Copy, Debug, Pod, Zeroable)]
 pub struct Open {
     pub amount_a: [u8; 8],
     pub amount_b: [u8; 8],
     pub expires_at: [u8; 8],
-    #[deprecated(since = "0.1.4", note = "Fee no longer supported")]
+    #[deprecated(since = "0.1.4", note = "Replaced by the protocol fee in Config")]
     pub fee: [u8; 8],
     pub id: [u8; 8],
+    pub is_atomic: [u8; 8],
+    pub is_listed: [u8; 8],
+    pub is_pro_rata: [u8; 8],
+    pub is_tokenized: [u8; 8],
+    pub lane: [u8; 2],
+    pub lot_size: [u8; 8],
+    pub merkle_root: [u8; 32],
+    pub min_fill: [u8; 8],
+    pub starts_at: [u8; 8],
+    pub taker: Pubkey,
+    pub tip: [u8; 8],
 }
 
 #[repr(C)]
 #[derive(Clone, Copy, Debug, Pod, Zeroable)]
 pub struct Fill {
     pub amount: [u8; 8],
+    pub referral_fee: [u8; 8],
+    pub tip: [u8; 8],
 }
 
 #[repr(C)]
 #[derive(Clone, Copy, Debug, Pod, Zeroable)]
 pub struct Redeem {}
 
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod, Zeroable)]
+pub struct Replace {
+    pub amount_a: [u8; 8],
+    pub amount_b: [u8; 8],
+    pub expires_at: [u8; 8],
+    pub id: [u8; 8],
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod, Zeroable)]
+pub struct TransferReceipt {}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod, Zeroable)]
+pub struct UpdateConfig {
+    pub admin: Pubkey,
+    pub fee: [u8; 8],
+    pub fee_collector: Pubkey,
+    pub is_paused: [u8; 8],
+}
+
+instruction!(ProtobookInstruction, Amend);
 instruction!(ProtobookInstruction, Cancel);
+instruction!(ProtobookInstruction, CancelAll);
 instruction!(ProtobookInstruction, Close);
 instruction!(ProtobookInstruction, Collect);
 instruction!(ProtobookInstruction, Fill);
+instruction!(ProtobookInstruction, InitMarket);
+instruction!(ProtobookInstruction, InitializeConfig);
+instruction!(ProtobookInstruction, Migrate);
 instruction!(ProtobookInstruction, Open);
 instruction!(ProtobookInstruction, Redeem);
+instruction!(ProtobookInstruction, Replace);
+instruction!(ProtobookInstruction, TransferReceipt);
+instruction!(ProtobookInstruction, UpdateConfig);
@@ -1,13 +1,23 @@
 pub mod consts;
+pub mod error;
+pub mod event;
 pub mod instruction;
+pub mod merkle;
+pub mod referral;
 pub mod sdk;
 pub mod state;
+pub mod token;
 
 pub mod prelude {
     pub use crate::consts::*;
+    pub use crate::error::*;
+    pub use crate::event::*;
     pub use crate::instruction::*;
+    pub use crate::merkle::*;
+    pub use crate::referral::*;
     pub use crate::sdk::*;
     pub use crate::state::*;
+    pub use crate::token::*;
 }
 
 use steel::*;
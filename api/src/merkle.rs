@@ -0,0 +1,128 @@
+use solana_program::hash::hashv;
+use steel::*;
+
+/// Domain separator of leaf hashes.
+const LEAF_PREFIX: &[u8] = &[0];
+
+/// Domain separator of node hashes.
+const NODE_PREFIX: &[u8] = &[1];
+
+/// Hashes a taker pubkey into a merkle leaf.
+pub fn hash_leaf(taker: &Pubkey) -> [u8; 32] {
+    hashv(&[LEAF_PREFIX, taker.as_ref()]).to_bytes()
+}
+
+/// Hashes a pair of merkle nodes. Nodes are sorted so proofs do not need to encode direction.
+pub fn hash_node(a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+    if a <= b {
+        hashv(&[NODE_PREFIX, a, b]).to_bytes()
+    } else {
+        hashv(&[NODE_PREFIX, b, a]).to_bytes()
+    }
+}
+
+/// Verifies a merkle proof for the given leaf against a root.
+pub fn verify_merkle_proof(root: &[u8; 32], leaf: [u8; 32], proof: &[[u8; 32]]) -> bool {
+    let node = proof
+        .iter()
+        .fold(leaf, |node, sibling| hash_node(&node, sibling));
+    node == *root
+}
+
+/// Computes the merkle root of a list of leaves.
+pub fn merkle_root(leaves: &[[u8; 32]]) -> [u8; 32] {
+    let mut level = leaves.to_vec();
+    while level.len() > 1 {
+        level = next_level(&level);
+    }
+    level.first().copied().unwrap_or_default()
+}
+
+/// Computes the merkle proof of the leaf at the given index.
+pub fn merkle_proof(leaves: &[[u8; 32]], index: usize) -> Vec<[u8; 32]> {
+    let mut proof = vec![];
+    let mut level = leaves.to_vec();
+    let mut index = index;
+    while level.len() > 1 {
+        if let Some(sibling) = level.get(index ^ 1) {
+            proof.push(*sibling);
+        }
+        level = next_level(&level);
+        index /= 2;
+    }
+    proof
+}
+
+/// Hashes a level of the tree into its parent level. An odd node is promoted unchanged.
+fn next_level(level: &[[u8; 32]]) -> Vec<[u8; 32]> {
+    level
+        .chunks(2)
+        .map(|pair| match pair {
+            [a, b] => hash_node(a, b),
+            [a] => *a,
+            _ => unreachable!(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn leaves(n: usize) -> Vec<[u8; 32]> {
+        (0..n).map(|_| hash_leaf(&Pubkey::new_unique())).collect()
+    }
+
+    #[test]
+    fn test_proofs_verify_for_every_leaf() {
+        for n in 1..=9 {
+            let leaves = leaves(n);
+            let root = merkle_root(&leaves);
+            for (i, leaf) in leaves.iter().enumerate() {
+                let proof = merkle_proof(&leaves, i);
+                assert!(verify_merkle_proof(&root, *leaf, &proof), "n={n} i={i}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_odd_node_is_promoted() {
+        let leaves = leaves(3);
+        let root = merkle_root(&leaves);
+        assert_eq!(
+            root,
+            hash_node(&hash_node(&leaves[0], &leaves[1]), &leaves[2])
+        );
+
+        // The promoted leaf has no sibling on the first level.
+        let proof = merkle_proof(&leaves, 2);
+        assert_eq!(proof, vec![hash_node(&leaves[0], &leaves[1])]);
+        assert!(verify_merkle_proof(&root, leaves[2], &proof));
+    }
+
+    #[test]
+    fn test_single_leaf_is_root() {
+        let leaves = leaves(1);
+        assert_eq!(merkle_root(&leaves), leaves[0]);
+        assert!(merkle_proof(&leaves, 0).is_empty());
+        assert!(verify_merkle_proof(&leaves[0], leaves[0], &[]));
+    }
+
+    #[test]
+    fn test_rejects_invalid_proofs() {
+        let leaves = leaves(4);
+        let root = merkle_root(&leaves);
+        let outsider = hash_leaf(&Pubkey::new_unique());
+        assert!(!verify_merkle_proof(
+            &root,
+            outsider,
+            &merkle_proof(&leaves, 0)
+        ));
+        assert!(!verify_merkle_proof(
+            &root,
+            leaves[0],
+            &merkle_proof(&leaves, 1)
+        ));
+        assert!(!verify_merkle_proof(&root, leaves[0], &[]));
+    }
+}
@@ -0,0 +1,52 @@
+use crate::consts::*;
+
+/// Splits a taker's deposit into the amount credited to the order and the referral fee.
+/// If the credited amount would exceed the given maximum, it is capped and the fee is scaled down with it.
+pub fn split_referral(amount: u64, referral_fee: u64, max_amount: u64) -> (u64, u64) {
+    let referral = (amount as u128 * referral_fee as u128 / DENOMINATOR_BPS as u128) as u64;
+    if amount - referral > max_amount {
+        let referral = (max_amount as u128 * referral_fee as u128
+            / (DENOMINATOR_BPS - referral_fee) as u128) as u64;
+        (max_amount, referral)
+    } else {
+        (amount - referral, referral)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_referral() {
+        assert_eq!(split_referral(10_000, 100, u64::MAX), (9_900, 100));
+        assert_eq!(split_referral(10_000, 0, u64::MAX), (10_000, 0));
+        assert_eq!(
+            split_referral(10_000, MAX_REFERRAL_FEE, u64::MAX),
+            (9_500, 500)
+        );
+    }
+
+    #[test]
+    fn test_split_referral_rounds_down() {
+        assert_eq!(split_referral(19, MAX_REFERRAL_FEE, u64::MAX), (19, 0));
+        assert_eq!(split_referral(99, 100, u64::MAX), (99, 0));
+        assert_eq!(split_referral(101, 100, u64::MAX), (100, 1));
+    }
+
+    #[test]
+    fn test_split_referral_at_cap() {
+        // A net deposit of exactly the maximum is not capped.
+        assert_eq!(split_referral(10_000, 100, 9_900), (9_900, 100));
+        assert_eq!(split_referral(1_000, 0, 1_000), (1_000, 0));
+    }
+
+    #[test]
+    fn test_split_referral_scales_fee_with_cap() {
+        // The capped deposit pays the same rate on the gross amount it stands for.
+        assert_eq!(split_referral(10_000, 100, 990), (990, 10));
+        assert_eq!(split_referral(10_000, MAX_REFERRAL_FEE, 1_000), (1_000, 52));
+        assert_eq!(split_referral(10_000, 0, 1_000), (1_000, 0));
+        assert_eq!(split_referral(10_000, 100, 0), (0, 0));
+    }
+}
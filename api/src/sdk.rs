@@ -1,8 +1,50 @@
-use spl_associated_token_account::get_associated_token_address;
+use solana_program::bpf_loader_upgradeable;
+use spl_associated_token_account::get_associated_token_address_with_program_id;
 use steel::*;
 
 use crate::prelude::*;
 
+// let [signer_info, config_info, maker_info, market_info, mint_a_info, order_info, sender_info, vault_a_info, token_program_a] =
+
+#[allow(clippy::too_many_arguments)]
+pub fn amend(
+    authority: Pubkey,
+    order: Pubkey,
+    mint_a: Pubkey,
+    mint_b: Pubkey,
+    token_program_a: Pubkey,
+    amount_a: u64,
+    amount_b: u64,
+    expires_at: i64,
+) -> Instruction {
+    let config_address = config_pda().0;
+    let maker_address = maker_pda(authority).0;
+    let market_address = market_pda(mint_a, mint_b).0;
+    let sender =
+        get_associated_token_address_with_program_id(&authority, &mint_a, &token_program_a);
+    let vault_a = get_associated_token_address_with_program_id(&order, &mint_a, &token_program_a);
+    Instruction {
+        program_id: crate::ID,
+        accounts: vec![
+            AccountMeta::new(authority, true),
+            AccountMeta::new_readonly(config_address, false),
+            AccountMeta::new_readonly(maker_address, false),
+            AccountMeta::new_readonly(market_address, false),
+            AccountMeta::new_readonly(mint_a, false),
+            AccountMeta::new(order, false),
+            AccountMeta::new(sender, false),
+            AccountMeta::new(vault_a, false),
+            AccountMeta::new_readonly(token_program_a, false),
+        ],
+        data: Amend {
+            amount_a: amount_a.to_le_bytes(),
+            amount_b: amount_b.to_le_bytes(),
+            expires_at: expires_at.to_le_bytes(),
+        }
+        .to_bytes(),
+    }
+}
+
 // let [signer_info, order_info] = accounts else {
 
 pub fn cancel(authority: Pubkey, order: Pubkey) -> Instruction {
@@ -16,106 +58,314 @@ pub fn cancel(authority: Pubkey, order: Pubkey) -> Instruction {
     }
 }
 
-// let [signer_info, beneficiary_a_info, beneficiary_b_info, mint_a_info, mint_b_info, order_info, vault_a_info, vault_b_info, system_program, token_program, associated_token_program] =
+// let [signer_info, maker_info, system_program] = accounts else {
 
-pub fn close(authority: Pubkey, order: Pubkey, mint_a: Pubkey, mint_b: Pubkey) -> Instruction {
-    let beneficiary_a = get_associated_token_address(&authority, &mint_a);
-    let beneficiary_b = get_associated_token_address(&authority, &mint_b);
-    let vault_a = get_associated_token_address(&order, &mint_a);
-    let vault_b = get_associated_token_address(&order, &mint_b);
+pub fn cancel_all(authority: Pubkey) -> Instruction {
+    let maker_address = maker_pda(authority).0;
     Instruction {
         program_id: crate::ID,
         accounts: vec![
             AccountMeta::new(authority, true),
+            AccountMeta::new(maker_address, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+        data: CancelAll {}.to_bytes(),
+    }
+}
+
+// let [signer_info, authority_info, beneficiary_a_info, beneficiary_b_info, mint_a_info, mint_b_info, order_info, owner_info, receipt_mint_info, unwrap_info, vault_a_info, vault_b_info, system_program, token_program_a, token_program_b, receipt_token_program, associated_token_program] =
+
+#[allow(clippy::too_many_arguments)]
+pub fn close(
+    signer: Pubkey,
+    authority: Pubkey,
+    owner: Pubkey,
+    order: Pubkey,
+    mint_a: Pubkey,
+    mint_b: Pubkey,
+    token_program_a: Pubkey,
+    token_program_b: Pubkey,
+) -> Instruction {
+    let beneficiary_a =
+        get_associated_token_address_with_program_id(&owner, &mint_a, &token_program_a);
+    let beneficiary_b =
+        get_associated_token_address_with_program_id(&owner, &mint_b, &token_program_b);
+    let receipt_mint = receipt_mint_pda(order).0;
+    let vault_a = get_associated_token_address_with_program_id(&order, &mint_a, &token_program_a);
+    let vault_b = get_associated_token_address_with_program_id(&order, &mint_b, &token_program_b);
+    let unwrap_address = unwrap_pda(order).0;
+    Instruction {
+        program_id: crate::ID,
+        accounts: vec![
+            AccountMeta::new(signer, true),
+            AccountMeta::new(authority, false),
             AccountMeta::new(beneficiary_a, false),
             AccountMeta::new(beneficiary_b, false),
             AccountMeta::new(mint_a, false),
             AccountMeta::new(mint_b, false),
             AccountMeta::new(order, false),
+            AccountMeta::new(owner, false),
+            AccountMeta::new(receipt_mint, false),
+            AccountMeta::new(unwrap_address, false),
             AccountMeta::new(vault_a, false),
             AccountMeta::new(vault_b, false),
             AccountMeta::new_readonly(system_program::ID, false),
-            AccountMeta::new_readonly(spl_token::ID, false),
+            AccountMeta::new_readonly(token_program_a, false),
+            AccountMeta::new_readonly(token_program_b, false),
+            AccountMeta::new_readonly(spl_token_2022::ID, false),
             AccountMeta::new_readonly(spl_associated_token_account::ID, false),
         ],
         data: Close {}.to_bytes(),
     }
 }
 
-// let [signer_info, beneficiary_info, fee_collector_info, mint_info, order_info, vault_info, system_program, token_program, associated_token_program] =
+// let [signer_info, authority_info, beneficiary_a_info, beneficiary_b_info, config_info, fee_info, fee_collector_info, maker_info, market_info, mint_a_info, mint_b_info, order_info, owner_info, unwrap_info, vault_a_info, vault_b_info, system_program, token_program_a, token_program_b, associated_token_program] =
 
-pub fn collect(authority: Pubkey, beneficiary: Pubkey, order: Pubkey, mint: Pubkey) -> Instruction {
-    let vault = get_associated_token_address(&order, &mint);
+#[allow(clippy::too_many_arguments)]
+pub fn collect(
+    signer: Pubkey,
+    authority: Pubkey,
+    owner: Pubkey,
+    order: Pubkey,
+    mint_a: Pubkey,
+    mint_b: Pubkey,
+    token_program_a: Pubkey,
+    token_program_b: Pubkey,
+    fee_collector: Pubkey,
+    is_listed: bool,
+) -> Instruction {
+    let beneficiary_a =
+        get_associated_token_address_with_program_id(&owner, &mint_a, &token_program_a);
+    let beneficiary_b =
+        get_associated_token_address_with_program_id(&owner, &mint_b, &token_program_b);
+    let config_address = config_pda().0;
+    let fee =
+        get_associated_token_address_with_program_id(&fee_collector, &mint_b, &token_program_b);
+    let maker_address = maker_pda(authority).0;
+    let market_address = market_pda(mint_a, mint_b).0;
+    let vault_a = get_associated_token_address_with_program_id(&order, &mint_a, &token_program_a);
+    let vault_b = get_associated_token_address_with_program_id(&order, &mint_b, &token_program_b);
+    let unwrap_address = unwrap_pda(order).0;
     Instruction {
         program_id: crate::ID,
         accounts: vec![
-            AccountMeta::new(authority, true),
-            AccountMeta::new(beneficiary, false),
-            AccountMeta::new(Pubkey::default(), false),
-            AccountMeta::new(mint, false),
+            AccountMeta::new(signer, true),
+            AccountMeta::new_readonly(authority, false),
+            AccountMeta::new(beneficiary_a, false),
+            AccountMeta::new(beneficiary_b, false),
+            AccountMeta::new_readonly(config_address, false),
+            AccountMeta::new(fee, false),
+            AccountMeta::new_readonly(fee_collector, false),
+            AccountMeta::new_readonly(maker_address, false),
+            AccountMeta {
+                pubkey: market_address,
+                is_signer: false,
+                is_writable: is_listed,
+            },
+            AccountMeta::new_readonly(mint_a, false),
+            AccountMeta::new_readonly(mint_b, false),
             AccountMeta::new(order, false),
-            AccountMeta::new(vault, false),
+            AccountMeta::new(owner, false),
+            AccountMeta::new(unwrap_address, false),
+            AccountMeta::new(vault_a, false),
+            AccountMeta::new(vault_b, false),
             AccountMeta::new_readonly(system_program::ID, false),
-            AccountMeta::new_readonly(spl_token::ID, false),
+            AccountMeta::new_readonly(token_program_a, false),
+            AccountMeta::new_readonly(token_program_b, false),
             AccountMeta::new_readonly(spl_associated_token_account::ID, false),
         ],
         data: Collect {}.to_bytes(),
     }
 }
 
-// let [signer_info, order_info, receipt_info, sender_info, vault_b_info, system_program, token_program] =
+// let [signer_info, beneficiary_info, config_info, fee_info, fee_collector_info, maker_info, mint_a_info, mint_b_info, order_info, receipt_info, receipt_mint_info, receipt_tokens_info, referrer_info, sender_info, unwrap_info, vault_a_info, vault_b_info, system_program, token_program_a, token_program_b, receipt_token_program, associated_token_program] =
+
+#[allow(clippy::too_many_arguments)]
+pub fn fill(
+    authority: Pubkey,
+    maker: Pubkey,
+    order: Pubkey,
+    mint_a: Pubkey,
+    mint_b: Pubkey,
+    token_program_a: Pubkey,
+    token_program_b: Pubkey,
+    fee_collector: Pubkey,
+    amount: u64,
+    tip: u64,
+    referrer: Option<Pubkey>,
+    referral_fee: u64,
+) -> Instruction {
+    fill_with_proof(
+        authority,
+        maker,
+        order,
+        mint_a,
+        mint_b,
+        token_program_a,
+        token_program_b,
+        fee_collector,
+        amount,
+        tip,
+        referrer,
+        referral_fee,
+        &[],
+    )
+}
 
-pub fn fill(authority: Pubkey, order: Pubkey, mint_b: Pubkey, amount: u64) -> Instruction {
-    let vault_b = get_associated_token_address(&order, &mint_b);
+#[allow(clippy::too_many_arguments)]
+pub fn fill_with_proof(
+    authority: Pubkey,
+    maker: Pubkey,
+    order: Pubkey,
+    mint_a: Pubkey,
+    mint_b: Pubkey,
+    token_program_a: Pubkey,
+    token_program_b: Pubkey,
+    fee_collector: Pubkey,
+    amount: u64,
+    tip: u64,
+    referrer: Option<Pubkey>,
+    referral_fee: u64,
+    proof: &[[u8; 32]],
+) -> Instruction {
+    let beneficiary =
+        get_associated_token_address_with_program_id(&authority, &mint_a, &token_program_a);
+    let config_address = config_pda().0;
+    let fee =
+        get_associated_token_address_with_program_id(&fee_collector, &mint_a, &token_program_a);
+    let maker_address = maker_pda(maker).0;
+    let vault_a = get_associated_token_address_with_program_id(&order, &mint_a, &token_program_a);
+    let vault_b = get_associated_token_address_with_program_id(&order, &mint_b, &token_program_b);
     let receipt_address = receipt_pda(authority, order).0;
-    let sender = get_associated_token_address(&authority, &mint_b);
+    let receipt_mint = receipt_mint_pda(order).0;
+    let receipt_tokens = get_associated_token_address_with_program_id(
+        &authority,
+        &receipt_mint,
+        &spl_token_2022::ID,
+    );
+    let sender =
+        get_associated_token_address_with_program_id(&authority, &mint_b, &token_program_b);
+    let unwrap_address = unwrap_pda(order).0;
     Instruction {
         program_id: crate::ID,
         accounts: vec![
             AccountMeta::new(authority, true),
+            AccountMeta::new(beneficiary, false),
+            AccountMeta::new_readonly(config_address, false),
+            AccountMeta::new(fee, false),
+            AccountMeta::new_readonly(fee_collector, false),
+            AccountMeta::new_readonly(maker_address, false),
+            AccountMeta::new_readonly(mint_a, false),
+            AccountMeta::new_readonly(mint_b, false),
             AccountMeta::new(order, false),
             AccountMeta::new(receipt_address, false),
+            AccountMeta::new(receipt_mint, false),
+            AccountMeta::new(receipt_tokens, false),
+            AccountMeta::new(referrer.unwrap_or_default(), false),
             AccountMeta::new(sender, false),
+            AccountMeta::new(unwrap_address, false),
+            AccountMeta::new(vault_a, false),
             AccountMeta::new(vault_b, false),
             AccountMeta::new_readonly(system_program::ID, false),
-            AccountMeta::new_readonly(spl_token::ID, false),
+            AccountMeta::new_readonly(token_program_a, false),
+            AccountMeta::new_readonly(token_program_b, false),
+            AccountMeta::new_readonly(spl_token_2022::ID, false),
+            AccountMeta::new_readonly(spl_associated_token_account::ID, false),
         ],
-        data: Fill {
-            amount: amount.to_le_bytes(),
-        }
-        .to_bytes(),
+        data: [
+            Fill {
+                amount: amount.to_le_bytes(),
+                referral_fee: referral_fee.to_le_bytes(),
+                tip: tip.to_le_bytes(),
+            }
+            .to_bytes(),
+            proof.concat(),
+        ]
+        .concat(),
     }
 }
 
-// let [signer_info, fee_collector_info, mint_a_info, mint_b_info, order_info, sender_info, vault_a_info, vault_b_info, system_program, token_program, associated_token_program] =
+// let [signer_info, account_info, system_program] = accounts else {
 
-#[allow(deprecated)]
+pub fn migrate(signer: Pubkey, account: Pubkey) -> Instruction {
+    Instruction {
+        program_id: crate::ID,
+        accounts: vec![
+            AccountMeta::new(signer, true),
+            AccountMeta::new(account, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+        data: Migrate {}.to_bytes(),
+    }
+}
+
+// let [signer_info, config_info, counter_info, maker_info, market_info, mint_a_info, mint_b_info, order_info, receipt_mint_info, sender_info, vault_a_info, vault_b_info, system_program, token_program_a, token_program_b, receipt_token_program, associated_token_program] =
+
+/// If a lane is given, the program assigns the id from that counter lane of the authority,
+/// and id must be the lane's next id so the order address can be derived.
+/// If listed, the order is listed on the market for its mints.
+#[allow(deprecated, clippy::too_many_arguments)]
 pub fn open(
     authority: Pubkey,
     amount_a: u64,
     amount_b: u64,
     expires_at: i64,
     id: u64,
+    is_atomic: bool,
+    is_listed: bool,
+    is_pro_rata: bool,
+    is_tokenized: bool,
+    lane: Option<u16>,
+    lot_size: u64,
+    min_fill: u64,
     mint_a: Pubkey,
     mint_b: Pubkey,
+    starts_at: i64,
+    tip: u64,
+    token_program_a: Pubkey,
+    token_program_b: Pubkey,
+    merkle_root: Option<[u8; 32]>,
+    taker: Option<Pubkey>,
 ) -> Instruction {
-    let sender = get_associated_token_address(&authority, &mint_a);
+    let sender =
+        get_associated_token_address_with_program_id(&authority, &mint_a, &token_program_a);
+    let config_address = config_pda().0;
+    let counter_address = counter_pda(authority, lane.unwrap_or_default()).0;
+    let maker_address = maker_pda(authority).0;
+    let market_address = market_pda(mint_a, mint_b).0;
     let order_address = order_pda(authority, id).0;
-    let vault_a = get_associated_token_address(&order_address, &mint_a);
-    let vault_b = get_associated_token_address(&order_address, &mint_b);
+    let receipt_mint = receipt_mint_pda(order_address).0;
+    let vault_a =
+        get_associated_token_address_with_program_id(&order_address, &mint_a, &token_program_a);
+    let vault_b =
+        get_associated_token_address_with_program_id(&order_address, &mint_b, &token_program_b);
     Instruction {
         program_id: crate::ID,
         accounts: vec![
             AccountMeta::new(authority, true),
-            AccountMeta::new(Pubkey::default(), false),
-            AccountMeta::new(mint_a, false),
-            AccountMeta::new(mint_b, false),
+            AccountMeta::new_readonly(config_address, false),
+            AccountMeta {
+                pubkey: counter_address,
+                is_signer: false,
+                is_writable: lane.is_some(),
+            },
+            AccountMeta::new(maker_address, false),
+            AccountMeta {
+                pubkey: market_address,
+                is_signer: false,
+                is_writable: is_listed,
+            },
+            AccountMeta::new_readonly(mint_a, false),
+            AccountMeta::new_readonly(mint_b, false),
             AccountMeta::new(order_address, false),
+            AccountMeta::new(receipt_mint, false),
             AccountMeta::new(sender, false),
             AccountMeta::new(vault_a, false),
             AccountMeta::new(vault_b, false),
             AccountMeta::new_readonly(system_program::ID, false),
-            AccountMeta::new_readonly(spl_token::ID, false),
+            AccountMeta::new_readonly(token_program_a, false),
+            AccountMeta::new_readonly(token_program_b, false),
+            AccountMeta::new_readonly(spl_token_2022::ID, false),
             AccountMeta::new_readonly(spl_associated_token_account::ID, false),
         ],
         data: Open {
@@ -123,30 +373,240 @@ pub fn open(
             amount_b: amount_b.to_le_bytes(),
             expires_at: expires_at.to_le_bytes(),
             fee: 0u64.to_le_bytes(),
-            id: id.to_le_bytes(),
+            id: if lane.is_some() { AUTO_ID } else { id }.to_le_bytes(),
+            is_atomic: (is_atomic as u64).to_le_bytes(),
+            is_listed: (is_listed as u64).to_le_bytes(),
+            is_pro_rata: (is_pro_rata as u64).to_le_bytes(),
+            is_tokenized: (is_tokenized as u64).to_le_bytes(),
+            lane: lane.unwrap_or_default().to_le_bytes(),
+            lot_size: lot_size.to_le_bytes(),
+            merkle_root: merkle_root.unwrap_or_default(),
+            min_fill: min_fill.to_le_bytes(),
+            starts_at: starts_at.to_le_bytes(),
+            taker: taker.unwrap_or_default(),
+            tip: tip.to_le_bytes(),
         }
         .to_bytes(),
     }
 }
 
-// let [signer_info, beneficiary_info, mint_info, order_info, receipt_info, vault_info, system_program, token_program, associated_token_program] =
+// let [signer_info, authority_info, beneficiary_info, config_info, fee_info, fee_collector_info, maker_info, mint_info, order_info, owner_info, receipt_info, receipt_mint_info, receipt_tokens_info, unwrap_info, vault_info, system_program, token_program, receipt_token_program, associated_token_program] =
 
-pub fn redeem(authority: Pubkey, beneficiary: Pubkey, order: Pubkey, mint: Pubkey) -> Instruction {
+#[allow(clippy::too_many_arguments)]
+pub fn redeem(
+    signer: Pubkey,
+    authority: Pubkey,
+    owner: Pubkey,
+    maker: Pubkey,
+    order: Pubkey,
+    mint: Pubkey,
+    token_program: Pubkey,
+    fee_collector: Pubkey,
+) -> Instruction {
+    let beneficiary = get_associated_token_address_with_program_id(&owner, &mint, &token_program);
+    let config_address = config_pda().0;
+    let fee = get_associated_token_address_with_program_id(&fee_collector, &mint, &token_program);
+    let maker_address = maker_pda(maker).0;
     let receipt_address = receipt_pda(authority, order).0;
-    let vault = get_associated_token_address(&order, &mint);
+    let receipt_mint = receipt_mint_pda(order).0;
+    let receipt_tokens = get_associated_token_address_with_program_id(
+        &authority,
+        &receipt_mint,
+        &spl_token_2022::ID,
+    );
+    let vault = get_associated_token_address_with_program_id(&order, &mint, &token_program);
+    let unwrap_address = unwrap_pda(order).0;
     Instruction {
         program_id: crate::ID,
         accounts: vec![
-            AccountMeta::new(authority, true),
+            AccountMeta::new(signer, true),
+            AccountMeta::new(authority, false),
             AccountMeta::new(beneficiary, false),
-            AccountMeta::new(mint, false),
+            AccountMeta::new_readonly(config_address, false),
+            AccountMeta::new(fee, false),
+            AccountMeta::new_readonly(fee_collector, false),
+            AccountMeta::new_readonly(maker_address, false),
+            AccountMeta::new_readonly(mint, false),
             AccountMeta::new(order, false),
+            AccountMeta::new(owner, false),
             AccountMeta::new(receipt_address, false),
+            AccountMeta::new(receipt_mint, false),
+            AccountMeta::new(receipt_tokens, false),
+            AccountMeta::new(unwrap_address, false),
             AccountMeta::new(vault, false),
             AccountMeta::new_readonly(system_program::ID, false),
-            AccountMeta::new_readonly(spl_token::ID, false),
+            AccountMeta::new_readonly(token_program, false),
+            AccountMeta::new_readonly(spl_token_2022::ID, false),
             AccountMeta::new_readonly(spl_associated_token_account::ID, false),
         ],
         data: Redeem {}.to_bytes(),
     }
 }
+
+// let [signer_info, beneficiary_b_info, config_info, fee_info, fee_collector_info, maker_info, market_info, mint_a_info, mint_b_info, new_order_info, new_vault_a_info, new_vault_b_info, order_info, sender_info, vault_a_info, vault_b_info, system_program, token_program_a, token_program_b, associated_token_program] =
+
+#[allow(clippy::too_many_arguments)]
+pub fn replace(
+    authority: Pubkey,
+    order: Pubkey,
+    amount_a: u64,
+    amount_b: u64,
+    expires_at: i64,
+    id: u64,
+    mint_a: Pubkey,
+    mint_b: Pubkey,
+    token_program_a: Pubkey,
+    token_program_b: Pubkey,
+    fee_collector: Pubkey,
+    is_listed: bool,
+) -> Instruction {
+    let beneficiary_b =
+        get_associated_token_address_with_program_id(&authority, &mint_b, &token_program_b);
+    let config_address = config_pda().0;
+    let fee =
+        get_associated_token_address_with_program_id(&fee_collector, &mint_b, &token_program_b);
+    let maker_address = maker_pda(authority).0;
+    let market_address = market_pda(mint_a, mint_b).0;
+    let new_order = order_pda(authority, id).0;
+    let new_vault_a =
+        get_associated_token_address_with_program_id(&new_order, &mint_a, &token_program_a);
+    let new_vault_b =
+        get_associated_token_address_with_program_id(&new_order, &mint_b, &token_program_b);
+    let sender =
+        get_associated_token_address_with_program_id(&authority, &mint_a, &token_program_a);
+    let vault_a = get_associated_token_address_with_program_id(&order, &mint_a, &token_program_a);
+    let vault_b = get_associated_token_address_with_program_id(&order, &mint_b, &token_program_b);
+    Instruction {
+        program_id: crate::ID,
+        accounts: vec![
+            AccountMeta::new(authority, true),
+            AccountMeta::new(beneficiary_b, false),
+            AccountMeta::new_readonly(config_address, false),
+            AccountMeta::new(fee, false),
+            AccountMeta::new_readonly(fee_collector, false),
+            AccountMeta::new(maker_address, false),
+            AccountMeta {
+                pubkey: market_address,
+                is_signer: false,
+                is_writable: is_listed,
+            },
+            AccountMeta::new_readonly(mint_a, false),
+            AccountMeta::new_readonly(mint_b, false),
+            AccountMeta::new(new_order, false),
+            AccountMeta::new(new_vault_a, false),
+            AccountMeta::new(new_vault_b, false),
+            AccountMeta::new(order, false),
+            AccountMeta::new(sender, false),
+            AccountMeta::new(vault_a, false),
+            AccountMeta::new(vault_b, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+            AccountMeta::new_readonly(token_program_a, false),
+            AccountMeta::new_readonly(token_program_b, false),
+            AccountMeta::new_readonly(spl_associated_token_account::ID, false),
+        ],
+        data: Replace {
+            amount_a: amount_a.to_le_bytes(),
+            amount_b: amount_b.to_le_bytes(),
+            expires_at: expires_at.to_le_bytes(),
+            id: id.to_le_bytes(),
+        }
+        .to_bytes(),
+    }
+}
+
+// let [signer_info, config_info, market_info, mint_a_info, mint_b_info, system_program] =
+
+pub fn init_market(
+    signer: Pubkey,
+    mint_a: Pubkey,
+    mint_b: Pubkey,
+    lot_size: u64,
+    min_order_size: u64,
+    tick_size: u64,
+) -> Instruction {
+    let config_address = config_pda().0;
+    let market_address = market_pda(mint_a, mint_b).0;
+    Instruction {
+        program_id: crate::ID,
+        accounts: vec![
+            AccountMeta::new(signer, true),
+            AccountMeta::new_readonly(config_address, false),
+            AccountMeta::new(market_address, false),
+            AccountMeta::new_readonly(mint_a, false),
+            AccountMeta::new_readonly(mint_b, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+        data: InitMarket {
+            lot_size: lot_size.to_le_bytes(),
+            min_order_size: min_order_size.to_le_bytes(),
+            tick_size: tick_size.to_le_bytes(),
+        }
+        .to_bytes(),
+    }
+}
+
+// let [signer_info, config_info, program_data_info, system_program] = accounts else {
+
+pub fn initialize_config(signer: Pubkey, fee: u64, fee_collector: Pubkey) -> Instruction {
+    let config_address = config_pda().0;
+    let program_data = bpf_loader_upgradeable::get_program_data_address(&crate::ID);
+    Instruction {
+        program_id: crate::ID,
+        accounts: vec![
+            AccountMeta::new(signer, true),
+            AccountMeta::new(config_address, false),
+            AccountMeta::new_readonly(program_data, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+        data: InitializeConfig {
+            fee: fee.to_le_bytes(),
+            fee_collector,
+        }
+        .to_bytes(),
+    }
+}
+
+// let [signer_info, new_authority_info, new_receipt_info, order_info, receipt_info, system_program] =
+
+pub fn transfer_receipt(authority: Pubkey, new_authority: Pubkey, order: Pubkey) -> Instruction {
+    let new_receipt_address = receipt_pda(new_authority, order).0;
+    let receipt_address = receipt_pda(authority, order).0;
+    Instruction {
+        program_id: crate::ID,
+        accounts: vec![
+            AccountMeta::new(authority, true),
+            AccountMeta::new_readonly(new_authority, false),
+            AccountMeta::new(new_receipt_address, false),
+            AccountMeta::new(order, false),
+            AccountMeta::new(receipt_address, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+        data: TransferReceipt {}.to_bytes(),
+    }
+}
+
+// let [signer_info, config_info] = accounts else {
+
+pub fn update_config(
+    signer: Pubkey,
+    admin: Pubkey,
+    fee: u64,
+    fee_collector: Pubkey,
+    is_paused: bool,
+) -> Instruction {
+    let config_address = config_pda().0;
+    Instruction {
+        program_id: crate::ID,
+        accounts: vec![
+            AccountMeta::new(signer, true),
+            AccountMeta::new(config_address, false),
+        ],
+        data: UpdateConfig {
+            admin,
+            fee: fee.to_le_bytes(),
+            fee_collector,
+            is_paused: (is_paused as u64).to_le_bytes(),
+        }
+        .to_bytes(),
+    }
+}
@@ -0,0 +1,115 @@
+use steel::*;
+
+use crate::{consts::*, error::ProtobookError};
+
+use super::ProtobookAccount;
+
+/// The config holds the global settings of the program.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+pub struct Config {
+    /// The authority allowed to update the config.
+    pub admin: Pubkey,
+
+    /// The protocol fee charged on filled proceeds, in basis points.
+    pub fee: u64,
+
+    /// The collector of the protocol fee.
+    pub fee_collector: Pubkey,
+
+    /// Whether opening, filling, amending, and replacing orders is paused. Withdrawals are never paused.
+    pub is_paused: u64,
+
+    /// The bump of the config PDA.
+    pub bump: u8,
+
+    /// Unused.
+    pub padding: [u8; 7],
+}
+
+impl Config {
+    /// Returns the protocol fee owed on the given proceeds.
+    pub fn fee_for(&self, amount: u64) -> u64 {
+        ((amount as u128 * self.fee as u128) / DENOMINATOR_BPS as u128) as u64
+    }
+
+    /// Returns the protocol fee owed on the given proceeds, checking the fee collector.
+    /// No fee is charged until the config has been initialized.
+    pub fn fee_of(
+        config_info: &AccountInfo,
+        fee_collector_info: &AccountInfo,
+        amount: u64,
+    ) -> Result<u64, ProgramError> {
+        if config_info.data_is_empty() {
+            config_info.has_seeds(&[CONFIG], &crate::ID)?;
+            return Ok(0);
+        }
+        let config = config_info.as_account::<Config>(&crate::ID)?.assert_err(
+            |c| c.fee_collector == *fee_collector_info.key,
+            ProtobookError::FeeCollectorMismatch.into(),
+        )?;
+        Ok(config.fee_for(amount))
+    }
+
+    /// Checks the program is not paused.
+    /// The program cannot be paused until the config has been initialized.
+    pub fn assert_unpaused(config_info: &AccountInfo) -> ProgramResult {
+        if config_info.data_is_empty() {
+            config_info.has_seeds(&[CONFIG], &crate::ID)?;
+            return Ok(());
+        }
+        config_info
+            .as_account::<Config>(&crate::ID)?
+            .assert_err(|c| c.is_paused == 0, ProtobookError::ProgramPaused.into())?;
+        Ok(())
+    }
+}
+
+account!(ProtobookAccount, Config);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::OrderV2;
+
+    fn new_config(fee: u64) -> Config {
+        Config {
+            fee,
+            ..Config::zeroed()
+        }
+    }
+
+    #[test]
+    fn test_fee_for_rounds_down() {
+        let config = new_config(30);
+        assert_eq!(config.fee_for(10_000), 30);
+        assert_eq!(config.fee_for(333), 0);
+        assert_eq!(config.fee_for(334), 1);
+        assert_eq!(new_config(MAX_FEE).fee_for(u64::MAX), u64::MAX / 10);
+        assert_eq!(new_config(0).fee_for(u64::MAX), 0);
+    }
+
+    #[test]
+    fn test_atomic_fill_fee_matches_redeem() {
+        // Takers pay the same fee on token A whether it is swapped on fill or redeemed at expiry.
+        let config = new_config(25);
+        let atomic = OrderV2 {
+            amount_a: 1_000_000,
+            amount_b: 300_000,
+            flags: ORDER_FLAG_ATOMIC,
+            ..OrderV2::zeroed()
+        };
+        let mut receipt = OrderV2 {
+            flags: ORDER_FLAG_PRO_RATA,
+            ..atomic
+        };
+        for deposit in [1, 3, 1_000, 123_457, 300_000] {
+            receipt.total_deposits = deposit;
+            assert!(receipt.is_swapped());
+            assert_eq!(
+                config.fee_for(atomic.amount_a_for(deposit)),
+                config.fee_for(receipt.amount_a_for(deposit))
+            );
+        }
+    }
+}
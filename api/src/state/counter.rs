@@ -0,0 +1,129 @@
+use steel::*;
+
+use crate::{consts::*, error::ProtobookError};
+
+use super::{counter_address, counter_pda, ProtobookAccount};
+
+/// A counter assigns sequential order ids to an authority from one of its lanes.
+/// Each lane counts through its own range of ids, so bots sharing a wallet never collide.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+pub struct Counter {
+    /// The authority of the orders.
+    pub authority: Pubkey,
+
+    /// The id assigned to the next order opened from this lane.
+    pub next_id: u64,
+
+    /// The lane of the counter.
+    pub lane: u16,
+
+    /// The bump of the counter PDA.
+    pub bump: u8,
+
+    /// Unused.
+    pub padding: [u8; 5],
+}
+
+impl Counter {
+    /// Returns the first id in the range of a lane.
+    pub fn first_id(lane: u16) -> u64 {
+        AUTO_ID_START + lane as u64 * LANE_SIZE
+    }
+
+    /// Returns the id that will be assigned to the next order opened from a lane.
+    /// Lanes without a counter account start at the beginning of their range.
+    pub fn next_id_of(counter: Option<&Self>, lane: u16) -> u64 {
+        counter.map_or(Self::first_id(lane), |c| c.next_id)
+    }
+
+    /// Assigns the next id of the lane.
+    /// The last id of each range is never assigned, so no assigned id is ever AUTO_ID.
+    pub fn assign_id(&mut self) -> Result<u64, ProgramError> {
+        let id = self.next_id;
+        if id - Self::first_id(self.lane) >= LANE_SIZE - 1 {
+            return Err(ProtobookError::LaneExhausted.into());
+        }
+        self.next_id += 1;
+        Ok(id)
+    }
+
+    /// Loads the counter of a lane of the signer, creating it if necessary.
+    /// An existing counter is checked against its stored bump.
+    pub fn load_or_create<'a, 'info>(
+        counter_info: &'a AccountInfo<'info>,
+        signer_info: &'a AccountInfo<'info>,
+        system_program: &'a AccountInfo<'info>,
+        lane: u16,
+    ) -> Result<&'a mut Counter, ProgramError> {
+        counter_info.is_writable()?;
+        if !counter_info.data_is_empty() {
+            let counter = counter_info.as_account_mut::<Counter>(&crate::ID)?;
+            counter_info.has_address(&counter_address(*signer_info.key, lane, counter.bump)?)?;
+            return Ok(counter);
+        }
+        let (counter_address, counter_bump) = counter_pda(*signer_info.key, lane);
+        counter_info.has_address(&counter_address)?;
+        create_program_account_with_bump::<Counter>(
+            counter_info,
+            system_program,
+            signer_info,
+            &crate::ID,
+            &[COUNTER, signer_info.key.as_ref(), &lane.to_le_bytes()],
+            counter_bump,
+        )?;
+        let counter = counter_info.as_account_mut::<Counter>(&crate::ID)?;
+        counter.authority = *signer_info.key;
+        counter.next_id = Self::first_id(lane);
+        counter.lane = lane;
+        counter.bump = counter_bump;
+        Ok(counter)
+    }
+}
+
+account!(ProtobookAccount, Counter);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn new_counter(lane: u16) -> Counter {
+        Counter {
+            next_id: Counter::first_id(lane),
+            lane,
+            ..Counter::zeroed()
+        }
+    }
+
+    #[test]
+    fn test_lanes_assign_disjoint_ids() {
+        let mut a = new_counter(0);
+        let mut b = new_counter(1);
+        assert_eq!(a.assign_id(), Ok(AUTO_ID_START));
+        assert_eq!(a.assign_id(), Ok(AUTO_ID_START + 1));
+        assert_eq!(b.assign_id(), Ok(AUTO_ID_START + LANE_SIZE));
+        assert_eq!(Counter::next_id_of(Some(&a), 0), AUTO_ID_START + 2);
+        assert_eq!(Counter::next_id_of(None, 1), AUTO_ID_START + LANE_SIZE);
+
+        // The lanes cover the top half of the id range exactly.
+        assert_eq!(Counter::first_id(u16::MAX), u64::MAX - LANE_SIZE + 1);
+    }
+
+    #[test]
+    fn test_lane_never_assigns_auto_id() {
+        let mut counter = new_counter(u16::MAX);
+        counter.next_id = u64::MAX - 1;
+        assert_eq!(counter.assign_id(), Ok(u64::MAX - 1));
+        assert_eq!(
+            counter.assign_id(),
+            Err(ProtobookError::LaneExhausted.into())
+        );
+        assert_eq!(counter.next_id, AUTO_ID);
+
+        // Other lanes stop one id short of the next lane's range.
+        let mut counter = new_counter(0);
+        counter.next_id = Counter::first_id(1) - 2;
+        assert!(counter.assign_id().is_ok());
+        assert!(counter.assign_id().is_err());
+    }
+}
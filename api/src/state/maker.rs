@@ -0,0 +1,72 @@
+use steel::*;
+
+use crate::{consts::*, error::ProtobookError};
+
+use super::{maker_pda, ProtobookAccount};
+
+/// A maker tracks the nonce used to cancel all orders of an authority at once.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+pub struct Maker {
+    /// The authority of the orders.
+    pub authority: Pubkey,
+
+    /// The current nonce. Orders opened under an older nonce can no longer be filled.
+    pub nonce: u64,
+
+    /// The bump of the maker PDA.
+    pub bump: u8,
+
+    /// Unused.
+    pub padding: [u8; 7],
+}
+
+impl Maker {
+    /// Returns the current nonce of an authority.
+    /// Authorities without a maker account have never advanced their nonce, so it is zero.
+    pub fn nonce_of(maker_info: &AccountInfo, authority: &Pubkey) -> Result<u64, ProgramError> {
+        if maker_info.data_is_empty() {
+            maker_info.has_seeds(&[MAKER, authority.as_ref()], &crate::ID)?;
+            return Ok(0);
+        }
+        let maker = maker_info.as_account::<Maker>(&crate::ID)?.assert_err(
+            |m| m.authority == *authority,
+            ProtobookError::NotAuthority.into(),
+        )?;
+        Ok(maker.nonce)
+    }
+
+    /// Loads the maker account of the signer, creating it if necessary.
+    pub fn load_or_create<'a, 'info>(
+        maker_info: &'a AccountInfo<'info>,
+        signer_info: &'a AccountInfo<'info>,
+        system_program: &'a AccountInfo<'info>,
+    ) -> Result<&'a mut Maker, ProgramError> {
+        maker_info.is_writable()?;
+        if !maker_info.data_is_empty() {
+            return maker_info
+                .as_account_mut::<Maker>(&crate::ID)?
+                .assert_mut_err(
+                    |m| m.authority == *signer_info.key,
+                    ProtobookError::NotAuthority.into(),
+                );
+        }
+        let (maker_address, maker_bump) = maker_pda(*signer_info.key);
+        maker_info.has_address(&maker_address)?;
+        create_program_account_with_bump::<Maker>(
+            maker_info,
+            system_program,
+            signer_info,
+            &crate::ID,
+            &[MAKER, signer_info.key.as_ref()],
+            maker_bump,
+        )?;
+        let maker = maker_info.as_account_mut::<Maker>(&crate::ID)?;
+        maker.authority = *signer_info.key;
+        maker.nonce = 0;
+        maker.bump = maker_bump;
+        Ok(maker)
+    }
+}
+
+account!(ProtobookAccount, Maker);
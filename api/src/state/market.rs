@@ -0,0 +1,106 @@
+use steel::*;
+
+use super::ProtobookAccount;
+
+/// A market lists orders offering token A for token B under shared trading rules.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+pub struct Market {
+    /// The mint of token A, offered by orders on the market.
+    pub mint_a: Pubkey,
+
+    /// The mint of token B, requested by orders on the market.
+    pub mint_b: Pubkey,
+
+    /// The increment of token A that orders must offer.
+    pub lot_size: u64,
+
+    /// The minimum amount of token A an order must offer.
+    pub min_order_size: u64,
+
+    /// The increment of token B that the price of one lot must be quoted in.
+    pub tick_size: u64,
+
+    /// The number of orders listed on the market that have not been collected.
+    pub live_orders: u64,
+
+    /// The total number of orders ever listed on the market.
+    pub total_orders: u64,
+
+    /// The bump of the market PDA.
+    pub bump: u8,
+
+    /// Unused.
+    pub padding: [u8; 7],
+}
+
+impl Market {
+    /// Returns true if an order offering amount_a for amount_b is on the market's price grid.
+    /// The order must offer a whole number of lots, at a price per lot that is a multiple of the tick size.
+    pub fn is_valid_order(&self, amount_a: u64, amount_b: u64) -> bool {
+        if amount_a < self.min_order_size || amount_a % self.lot_size != 0 {
+            return false;
+        }
+        let lots = (amount_a / self.lot_size) as u128;
+        amount_b as u128 % (lots * self.tick_size as u128) == 0
+    }
+}
+
+account!(ProtobookAccount, Market);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn market() -> Market {
+        Market {
+            lot_size: 100,
+            min_order_size: 200,
+            tick_size: 5,
+            ..Market::zeroed()
+        }
+    }
+
+    #[test]
+    fn test_order_must_meet_min_order_size() {
+        let market = market();
+        assert!(!market.is_valid_order(0, 10));
+        assert!(!market.is_valid_order(100, 5));
+        assert!(market.is_valid_order(200, 10));
+    }
+
+    #[test]
+    fn test_order_must_offer_whole_lots() {
+        let market = market();
+        assert!(market.is_valid_order(300, 15));
+        assert!(!market.is_valid_order(250, 15));
+        assert!(!market.is_valid_order(301, 15));
+    }
+
+    #[test]
+    fn test_order_price_per_lot_must_be_on_tick_grid() {
+        let market = market();
+
+        // Three lots at one tick each, then at two ticks each.
+        assert!(market.is_valid_order(300, 15));
+        assert!(market.is_valid_order(300, 30));
+
+        // A total that is a multiple of the tick but not of the lot count is off the grid.
+        assert!(!market.is_valid_order(300, 20));
+        assert!(!market.is_valid_order(300, 16));
+    }
+
+    #[test]
+    fn test_order_does_not_overflow() {
+        let market = Market {
+            lot_size: 1,
+            min_order_size: 1,
+            tick_size: u64::MAX,
+            ..Market::zeroed()
+        };
+        // The tick of every lot together exceeds u64, but is computed in u128.
+        assert!(!market.is_valid_order(u64::MAX, u64::MAX));
+        assert!(!market.is_valid_order(2, u64::MAX));
+        assert!(market.is_valid_order(1, u64::MAX));
+    }
+}
@@ -1,7 +1,19 @@
-mod order;
-mod receipt;
-pub use order::*;
-pub use receipt::*;
+mod config;
+mod counter;
+mod maker;
+mod market;
+mod order_v1;
+mod order_v2;
+mod receipt_v1;
+mod receipt_v2;
+pub use config::*;
+pub use counter::*;
+pub use maker::*;
+pub use market::*;
+pub use order_v1::*;
+pub use order_v2::*;
+pub use receipt_v1::*;
+pub use receipt_v2::*;
 
 use steel::*;
 
@@ -10,8 +22,46 @@ use crate::consts::*;
 #[repr(u8)]
 #[derive(Clone, Copy, Debug, Eq, PartialEq, IntoPrimitive, TryFromPrimitive)]
 pub enum ProtobookAccount {
-    Order = 0,
-    Receipt = 1,
+    OrderV1 = 0,
+    ReceiptV1 = 1,
+    OrderV2 = 2,
+    Maker = 3,
+    ReceiptV2 = 4,
+    Config = 5,
+    Counter = 6,
+    Market = 7,
+}
+
+/// Fetch PDA of the config account.
+pub fn config_pda() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[CONFIG], &crate::id())
+}
+
+/// Fetch PDA of a counter lane of an authority.
+pub fn counter_pda(authority: Pubkey, lane: u16) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[COUNTER, authority.as_ref(), &lane.to_le_bytes()],
+        &crate::id(),
+    )
+}
+
+/// Derive the address of a counter account from its stored bump.
+pub fn counter_address(authority: Pubkey, lane: u16, bump: u8) -> Result<Pubkey, ProgramError> {
+    Pubkey::create_program_address(
+        &[COUNTER, authority.as_ref(), &lane.to_le_bytes(), &[bump]],
+        &crate::id(),
+    )
+    .map_err(|_| ProgramError::InvalidSeeds)
+}
+
+/// Fetch PDA of the maker account.
+pub fn maker_pda(authority: Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[MAKER, authority.as_ref()], &crate::id())
+}
+
+/// Fetch PDA of the market for orders offering token A for token B.
+pub fn market_pda(mint_a: Pubkey, mint_b: Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[MARKET, mint_a.as_ref(), mint_b.as_ref()], &crate::id())
 }
 
 /// Fetch PDA of the order account.
@@ -26,3 +76,44 @@ pub fn order_pda(authority: Pubkey, id: u64) -> (Pubkey, u8) {
 pub fn receipt_pda(authority: Pubkey, order: Pubkey) -> (Pubkey, u8) {
     Pubkey::find_program_address(&[RECEIPT, authority.as_ref(), order.as_ref()], &crate::id())
 }
+
+/// Derive the address of a receipt account from its stored bump.
+pub fn receipt_address(authority: Pubkey, order: Pubkey, bump: u8) -> Result<Pubkey, ProgramError> {
+    Pubkey::create_program_address(
+        &[RECEIPT, authority.as_ref(), order.as_ref(), &[bump]],
+        &crate::id(),
+    )
+    .map_err(|_| ProgramError::InvalidSeeds)
+}
+
+/// Fetch PDA of the temporary token account used to unwrap native SOL.
+pub fn unwrap_pda(order: Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[UNWRAP, order.as_ref()], &crate::id())
+}
+
+/// Fetch PDA of the receipt mint.
+pub fn receipt_mint_pda(order: Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[RECEIPT_MINT, order.as_ref()], &crate::id())
+}
+
+/// Derive the address of an order's receipt mint from its stored bump.
+pub fn receipt_mint_address(order: Pubkey, bump: u8) -> Result<Pubkey, ProgramError> {
+    Pubkey::create_program_address(&[RECEIPT_MINT, order.as_ref(), &[bump]], &crate::id())
+        .map_err(|_| ProgramError::InvalidSeeds)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stored_bumps_derive_pdas() {
+        let authority = Pubkey::new_unique();
+        let order = Pubkey::new_unique();
+        let (address, bump) = receipt_pda(authority, order);
+        assert_eq!(receipt_address(authority, order, bump), Ok(address));
+        assert!(receipt_address(Pubkey::new_unique(), order, bump) != Ok(address));
+        let (address, bump) = receipt_mint_pda(order);
+        assert_eq!(receipt_mint_address(order, bump), Ok(address));
+    }
+}
@@ -2,10 +2,11 @@ use steel::*;
 
 use super::ProtobookAccount;
 
-/// An order is a public, timebound offer to buy a given token at a fixed price.
+/// The original layout of an order account.
+/// Orders opened before [`OrderV2`](super::OrderV2) keep this layout, and handlers read them through it.
 #[repr(C)]
 #[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
-pub struct Order {
+pub struct OrderV1 {
     /// The maker of the order.
     pub authority: Pubkey,
 
@@ -19,11 +20,11 @@ pub struct Order {
     pub expires_at: i64,
 
     /// An optional fee to be paid by the authority if the order is filled.
-    #[deprecated(since = "0.1.4", note = "Fee no longer supported")]
+    #[deprecated(since = "0.1.4", note = "Replaced by the protocol fee in Config")]
     pub fee: u64,
 
     /// The collector of the fee.
-    #[deprecated(since = "0.1.4", note = "Fee no longer supported")]
+    #[deprecated(since = "0.1.4", note = "Replaced by the protocol fee in Config")]
     pub fee_collector: Pubkey,
 
     /// A unique identifier for the order, namespaced by the authority.
@@ -48,4 +49,4 @@ pub struct Order {
     pub is_collected: u64,
 }
 
-account!(ProtobookAccount, Order);
+account!(ProtobookAccount, OrderV1);
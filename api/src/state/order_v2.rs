@@ -0,0 +1,522 @@
+use steel::*;
+
+use crate::consts::*;
+use crate::merkle::*;
+
+use super::{order_pda, OrderV1, ProtobookAccount};
+
+/// The lifecycle status of an order.
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, IntoPrimitive, TryFromPrimitive)]
+pub enum OrderStatus {
+    /// The order is open, or has expired but has not been collected yet.
+    Open = 0,
+
+    /// The order was cancelled by its authority and has not been collected yet.
+    Cancelled = 1,
+
+    /// The order has been collected by its authority.
+    Collected = 2,
+}
+
+/// An order is a public, timebound offer to buy a given token at a fixed price.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+pub struct OrderV2 {
+    /// The version of the account layout.
+    pub version: u8,
+
+    /// The lifecycle status of the order.
+    pub status: u8,
+
+    /// The bump of the order PDA.
+    pub bump: u8,
+
+    /// The bump of the receipt mint PDA, if the order is tokenized.
+    pub receipt_mint_bump: u8,
+
+    /// A bitfield of the order's modes.
+    pub flags: u32,
+
+    /// The maker of the order.
+    pub authority: Pubkey,
+
+    /// The amount of token A offered by the authority and locked in escrow.
+    pub amount_a: u64,
+
+    /// The amount of token B requested by the authority.
+    pub amount_b: u64,
+
+    /// The time at which the order expires.
+    pub expires_at: i64,
+
+    /// A unique identifier for the order, namespaced by the authority.
+    pub id: u64,
+
+    /// The mint of token A.
+    pub mint_a: Pubkey,
+
+    /// The mint of token B.
+    pub mint_b: Pubkey,
+
+    /// The total amount of token B deposited by takers.
+    pub total_deposits: u64,
+
+    /// The total number receipts issued for this order.
+    pub total_receipts: u64,
+
+    /// The total amount of receipts redeemed by takers.
+    pub total_redeemed: u64,
+
+    /// The only taker allowed to fill the order, or the default pubkey if anyone may fill it.
+    pub taker: Pubkey,
+
+    /// The merkle root of takers allowed to fill the order, or zero if anyone may fill it.
+    pub merkle_root: [u8; 32],
+
+    /// The minimum amount of token B a taker must deposit in one fill.
+    pub min_fill: u64,
+
+    /// The increment of token B a taker must deposit in, or zero if any amount is allowed.
+    pub lot_size: u64,
+
+    /// The time at which the order starts accepting fills.
+    pub starts_at: i64,
+
+    /// The nonce of the authority's maker account when the order was opened.
+    pub nonce: u64,
+
+    /// The lamports escrowed to reward whoever collects the order.
+    pub tip: u64,
+
+    /// The market the order is listed on, or the default pubkey if it is unlisted.
+    pub market: Pubkey,
+
+    /// Reserved for future fields.
+    pub reserved: [u8; 96],
+}
+
+/// The current layout of an order account.
+#[deprecated(since = "0.1.5", note = "Use OrderV2")]
+pub type Order = OrderV2;
+
+impl OrderV2 {
+    /// Reads an order from account data of either layout.
+    /// Orders in the original layout are upgraded in memory, with their bump left at zero.
+    pub fn try_from_any(data: &[u8]) -> Result<Self, ProgramError> {
+        match data.first().map(|d| ProtobookAccount::try_from(*d)) {
+            Some(Ok(ProtobookAccount::OrderV1)) => Ok(Self::from(*OrderV1::try_from_bytes(data)?)),
+            _ => Ok(*Self::try_from_bytes(data)?),
+        }
+    }
+
+    /// Loads an order account of either layout.
+    /// Orders in the original layout have no stored bump, so it is derived here until they are migrated.
+    pub fn load(order_info: &AccountInfo) -> Result<Self, ProgramError> {
+        order_info.has_owner(&crate::ID)?;
+        let data = order_info.try_borrow_data()?;
+        let mut order = Self::try_from_any(&data)?;
+        if data[0] == OrderV1::discriminator() {
+            order.bump = order_pda(order.authority, order.id).1;
+        }
+        Ok(order)
+    }
+
+    /// Writes the order back to its account, keeping the layout the account already has.
+    /// Orders in the original layout only store the fields that layout has.
+    #[allow(deprecated)]
+    pub fn save(&self, order_info: &AccountInfo) -> ProgramResult {
+        order_info.is_writable()?;
+        let mut data = order_info.try_borrow_mut_data()?;
+        if data.first() == Some(&OrderV1::discriminator()) {
+            let order = OrderV1::try_from_bytes_mut(&mut data)?;
+            order.amount_a = self.amount_a;
+            order.amount_b = self.amount_b;
+            order.expires_at = self.expires_at;
+            order.total_deposits = self.total_deposits;
+            order.total_receipts = self.total_receipts;
+            order.total_redeemed = self.total_redeemed;
+            order.is_collected = self.is_collected() as u64;
+        } else {
+            *Self::try_from_bytes_mut(&mut data)? = *self;
+        }
+        Ok(())
+    }
+
+    /// Returns the account data of an order in the original layout, upgraded to the current one.
+    /// The account must be resized to the length of the returned data before it is written.
+    pub fn migrate(data: &[u8], bump: u8) -> Result<Vec<u8>, ProgramError> {
+        let order = Self {
+            bump,
+            ..Self::from(*OrderV1::try_from_bytes(data)?)
+        };
+        let mut data = vec![0; 8 + std::mem::size_of::<Self>()];
+        data[0] = Self::discriminator();
+        data[8..].copy_from_slice(order.to_bytes());
+        Ok(data)
+    }
+
+    /// Returns the lifecycle status of the order.
+    pub fn status(&self) -> OrderStatus {
+        OrderStatus::try_from(self.status).unwrap_or(OrderStatus::Open)
+    }
+
+    /// Returns true if the order has been collected.
+    pub fn is_collected(&self) -> bool {
+        self.status() == OrderStatus::Collected
+    }
+
+    /// Returns true if a partially filled order settles pro rata at expiry.
+    pub fn is_pro_rata(&self) -> bool {
+        self.flags & ORDER_FLAG_PRO_RATA != 0
+    }
+
+    /// Returns true if fills swap token A to the taker immediately, without issuing receipts.
+    pub fn is_atomic(&self) -> bool {
+        self.flags & ORDER_FLAG_ATOMIC != 0
+    }
+
+    /// Returns true if fills mint receipt tokens instead of receipt accounts.
+    pub fn is_tokenized(&self) -> bool {
+        self.flags & ORDER_FLAG_TOKENIZED != 0
+    }
+
+    /// Returns true if the order is listed on a market.
+    pub fn is_listed(&self) -> bool {
+        self.market != Pubkey::default()
+    }
+
+    /// Returns true if the given taker is allowed to fill the order.
+    pub fn is_taker(&self, taker: &Pubkey) -> bool {
+        self.taker == Pubkey::default() || self.taker == *taker
+    }
+
+    /// Returns true if the given taker is proven to be on the order's allowlist.
+    pub fn is_allowlisted(&self, taker: &Pubkey, proof: &[[u8; 32]]) -> bool {
+        self.merkle_root == [0; 32]
+            || verify_merkle_proof(&self.merkle_root, hash_leaf(taker), proof)
+    }
+
+    /// Returns true if a deposit of the given amount satisfies the minimum fill and lot size.
+    /// A deposit of the remaining balance is always allowed when it is smaller than one lot.
+    pub fn is_valid_fill(&self, amount: u64) -> bool {
+        let remaining = self.amount_b - self.total_deposits;
+        let is_remainder = amount == remaining && remaining < self.lot_size.max(self.min_fill);
+        amount > 0
+            && (is_remainder
+                || (amount >= self.min_fill && (self.lot_size == 0 || amount % self.lot_size == 0)))
+    }
+
+    /// Returns true if the order has expired, or was pulled from the book by advancing its maker's nonce.
+    pub fn is_expired(&self, now: i64, maker_nonce: u64) -> bool {
+        self.expires_at < now || self.nonce != maker_nonce
+    }
+
+    /// Returns true if the order has been completely filled.
+    pub fn is_filled(&self) -> bool {
+        self.total_deposits == self.amount_b
+    }
+
+    /// Returns true if deposits are swapped for token A at expiry, rather than refunded.
+    pub fn is_swapped(&self) -> bool {
+        self.is_filled() || ((self.is_pro_rata() || self.is_atomic()) && self.total_deposits > 0)
+    }
+
+    /// Returns the amounts of token A and token B owed to the authority once the order expires.
+    /// Atomic orders pay out token A as they are filled, so the balance of vault A is unsold.
+    pub fn collectable(&self, vault_a_amount: u64) -> (u64, u64) {
+        if self.is_atomic() {
+            (vault_a_amount, self.total_deposits)
+        } else if self.is_swapped() {
+            let sold = self.amount_a_for(self.total_deposits);
+            (self.amount_a - sold, self.total_deposits)
+        } else {
+            (self.amount_a, 0)
+        }
+    }
+
+    /// Returns the amount of token A owed for a given deposit of token B.
+    pub fn amount_a_for(&self, deposit: u64) -> u64 {
+        ((self.amount_a as u128 * deposit as u128) / self.amount_b as u128) as u64
+    }
+}
+
+impl From<OrderV1> for OrderV2 {
+    /// Orders in the original layout predate every mode, so they upgrade with none set.
+    fn from(order: OrderV1) -> Self {
+        let status = if order.is_collected == 1 {
+            OrderStatus::Collected
+        } else {
+            OrderStatus::Open
+        };
+        Self {
+            version: ORDER_VERSION,
+            status: status.into(),
+            bump: 0,
+            receipt_mint_bump: 0,
+            flags: 0,
+            authority: order.authority,
+            amount_a: order.amount_a,
+            amount_b: order.amount_b,
+            expires_at: order.expires_at,
+            id: order.id,
+            mint_a: order.mint_a,
+            mint_b: order.mint_b,
+            total_deposits: order.total_deposits,
+            total_receipts: order.total_receipts,
+            total_redeemed: order.total_redeemed,
+            taker: Pubkey::default(),
+            merkle_root: [0; 32],
+            min_fill: 0,
+            lot_size: 0,
+            starts_at: 0,
+            nonce: 0,
+            tip: 0,
+            market: Pubkey::default(),
+            reserved: [0; 96],
+        }
+    }
+}
+
+account!(ProtobookAccount, OrderV2);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn new_order(amount_a: u64, amount_b: u64, flags: u32) -> OrderV2 {
+        OrderV2 {
+            amount_a,
+            amount_b,
+            flags,
+            ..OrderV2::zeroed()
+        }
+    }
+
+    #[test]
+    fn test_stale_nonce_expires_order() {
+        let mut order = new_order(1_000, 1_000, 0);
+        order.expires_at = 100;
+        order.nonce = 1;
+        assert!(!order.is_expired(50, 1));
+        assert!(order.is_expired(50, 2));
+        assert!(order.is_expired(150, 1));
+    }
+
+    #[test]
+    fn test_fill_respects_min_fill_and_lot_size() {
+        let mut order = new_order(1_000, 1_000, 0);
+        order.min_fill = 200;
+        order.lot_size = 100;
+        assert!(order.is_valid_fill(200));
+        assert!(order.is_valid_fill(300));
+        assert!(!order.is_valid_fill(0));
+        assert!(!order.is_valid_fill(100));
+        assert!(!order.is_valid_fill(250));
+    }
+
+    #[test]
+    fn test_fill_allows_remainder_below_one_lot() {
+        let mut order = new_order(1_000, 1_000, 0);
+        order.min_fill = 200;
+        order.lot_size = 100;
+
+        // A remainder smaller than the minimum fill may be deposited, but only in full.
+        order.total_deposits = 950;
+        assert!(order.is_valid_fill(50));
+        assert!(!order.is_valid_fill(40));
+        order.total_deposits = 900;
+        assert!(order.is_valid_fill(100));
+
+        // A remainder of exactly the minimum fill is a regular fill.
+        order.total_deposits = 800;
+        assert!(order.is_valid_fill(200));
+        assert!(!order.is_valid_fill(150));
+    }
+
+    #[test]
+    fn test_fill_remainder_boundary_without_min_fill() {
+        let mut order = new_order(1_000, 1_000, 0);
+        order.lot_size = 100;
+        order.total_deposits = 850;
+        assert!(!order.is_valid_fill(150));
+        assert!(order.is_valid_fill(100));
+        order.total_deposits = 901;
+        assert!(order.is_valid_fill(99));
+        assert!(!order.is_valid_fill(98));
+    }
+
+    #[test]
+    fn test_fill_without_rules_accepts_any_amount() {
+        let order = new_order(1_000, 1_000, 0);
+        assert!(order.is_valid_fill(1));
+        assert!(order.is_valid_fill(999));
+        assert!(!order.is_valid_fill(0));
+    }
+
+    #[test]
+    fn test_amount_a_for_rounds_down() {
+        let order = new_order(10, 3, 0);
+        assert_eq!(order.amount_a_for(1), 3);
+        assert_eq!(order.amount_a_for(2), 6);
+        assert_eq!(order.amount_a_for(3), 10);
+        let order = new_order(u64::MAX, u64::MAX, 0);
+        assert_eq!(order.amount_a_for(u64::MAX), u64::MAX);
+    }
+
+    #[test]
+    fn test_unfilled_order_is_refunded() {
+        let mut order = new_order(1_000, 500, 0);
+        order.total_deposits = 250;
+        assert!(!order.is_swapped());
+        order.total_deposits = 500;
+        assert!(order.is_swapped());
+    }
+
+    #[test]
+    fn test_partially_filled_order_settles_pro_rata() {
+        let mut order = new_order(1_000, 500, ORDER_FLAG_PRO_RATA);
+        assert!(!order.is_swapped());
+        order.total_deposits = 250;
+        assert!(order.is_swapped());
+        assert_eq!(order.amount_a_for(order.total_deposits), 500);
+
+        // Rounding leaves dust in the vault rather than overpaying takers.
+        let mut order = new_order(10, 3, ORDER_FLAG_PRO_RATA);
+        order.total_deposits = 1;
+        assert_eq!(order.amount_a_for(order.total_deposits), 3);
+    }
+
+    #[test]
+    fn test_collectable_unfilled_order_is_refunded() {
+        let mut order = new_order(1_000, 500, 0);
+        order.total_deposits = 250;
+        assert_eq!(order.collectable(1_000), (1_000, 0));
+    }
+
+    #[test]
+    fn test_collectable_filled_order_is_swapped() {
+        let mut order = new_order(1_000, 500, 0);
+        order.total_deposits = 500;
+        assert_eq!(order.collectable(1_000), (0, 500));
+    }
+
+    #[test]
+    fn test_collectable_pro_rata_order() {
+        // The authority keeps the deposits and the unsold share of token A.
+        let mut order = new_order(1_000, 500, ORDER_FLAG_PRO_RATA);
+        order.total_deposits = 250;
+        assert_eq!(order.collectable(1_000), (500, 250));
+
+        // Rounding leaves dust in the vault rather than overpaying the authority.
+        let mut order = new_order(10, 3, ORDER_FLAG_PRO_RATA);
+        order.total_deposits = 1;
+        assert_eq!(order.collectable(10), (7, 1));
+    }
+
+    #[test]
+    fn test_collectable_atomic_order() {
+        // Token A is paid out on each fill, so the vault holds exactly the unsold amount.
+        let mut order = new_order(1_000, 500, ORDER_FLAG_ATOMIC);
+        order.total_deposits = 250;
+        assert_eq!(order.collectable(500), (500, 250));
+        order.total_deposits = 0;
+        assert_eq!(order.collectable(1_000), (1_000, 0));
+    }
+
+    #[test]
+    fn test_atomic_order_is_swapped_once_filled() {
+        let mut order = new_order(1_000, 500, ORDER_FLAG_ATOMIC);
+        assert!(!order.is_swapped());
+        order.total_deposits = 1;
+        assert!(order.is_swapped());
+    }
+
+    #[allow(deprecated)]
+    fn new_legacy_order(is_collected: u64) -> OrderV1 {
+        OrderV1 {
+            authority: Pubkey::new_unique(),
+            amount_a: 1_000,
+            amount_b: 500,
+            expires_at: 100,
+            fee: 25,
+            fee_collector: Pubkey::new_unique(),
+            id: 7,
+            mint_a: Pubkey::new_unique(),
+            mint_b: Pubkey::new_unique(),
+            total_deposits: 250,
+            total_receipts: 2,
+            total_redeemed: 1,
+            is_collected,
+        }
+    }
+
+    fn account_data<T: Discriminator + Pod>(account: &T) -> Vec<u8> {
+        let mut data = vec![T::discriminator(), 0, 0, 0, 0, 0, 0, 0];
+        data.extend_from_slice(bytemuck::bytes_of(account));
+        data
+    }
+
+    #[test]
+    fn test_legacy_order_upgrades_without_modes() {
+        let legacy = new_legacy_order(0);
+        let order = OrderV2::from(legacy);
+        assert_eq!(order.version, ORDER_VERSION);
+        assert_eq!(order.status(), OrderStatus::Open);
+        assert_eq!(order.flags, 0);
+        assert_eq!(order.authority, legacy.authority);
+        assert_eq!(order.amount_a, 1_000);
+        assert_eq!(order.amount_b, 500);
+        assert_eq!(order.expires_at, 100);
+        assert_eq!(order.id, 7);
+        assert_eq!(order.mint_a, legacy.mint_a);
+        assert_eq!(order.mint_b, legacy.mint_b);
+        assert_eq!(order.total_deposits, 250);
+        assert_eq!(order.total_receipts, 2);
+        assert_eq!(order.total_redeemed, 1);
+        assert_eq!(order.taker, Pubkey::default());
+        assert_eq!(order.nonce, 0);
+        assert!(!order.is_swapped());
+
+        let order = OrderV2::from(new_legacy_order(1));
+        assert!(order.is_collected());
+    }
+
+    #[test]
+    fn test_order_reads_either_layout() {
+        let legacy = new_legacy_order(1);
+        let order = OrderV2::try_from_any(&account_data(&legacy)).unwrap();
+        assert_eq!(order, OrderV2::from(legacy));
+
+        let current = OrderV2 {
+            bump: 255,
+            flags: ORDER_FLAG_ATOMIC,
+            tip: 5,
+            ..order
+        };
+        assert_eq!(OrderV2::try_from_any(&account_data(&current)).unwrap(), current);
+
+        // Accounts of any other type are rejected.
+        let receipt = crate::state::ReceiptV2::zeroed();
+        assert!(OrderV2::try_from_any(&account_data(&receipt)).is_err());
+    }
+
+    #[test]
+    fn test_migrate_order_grows_to_current_layout() {
+        let legacy = new_legacy_order(0);
+        let data = account_data(&legacy);
+        let migrated = OrderV2::migrate(&data, 254).unwrap();
+        assert_eq!(migrated.len(), 8 + std::mem::size_of::<OrderV2>());
+        assert_eq!(migrated.len() - data.len(), 192);
+        assert_eq!(
+            *OrderV2::try_from_bytes(&migrated).unwrap(),
+            OrderV2 {
+                bump: 254,
+                ..OrderV2::from(legacy)
+            }
+        );
+
+        // Orders already in the current layout cannot be migrated again.
+        assert!(OrderV2::migrate(&migrated, 254).is_err());
+    }
+}
@@ -2,10 +2,11 @@ use steel::*;
 
 use super::ProtobookAccount;
 
-/// A receipt tracks a deposit to fill an order.
+/// The original layout of a receipt account.
+/// Receipts issued before [`ReceiptV2`](super::ReceiptV2) keep this layout, and handlers read them through it.
 #[repr(C)]
 #[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
-pub struct Receipt {
+pub struct ReceiptV1 {
     /// The holder of the receipt (order taker).
     pub authority: Pubkey,
 
@@ -16,4 +17,4 @@ pub struct Receipt {
     pub order: Pubkey,
 }
 
-account!(ProtobookAccount, Receipt);
+account!(ProtobookAccount, ReceiptV1);
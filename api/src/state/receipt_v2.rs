@@ -0,0 +1,149 @@
+use steel::*;
+
+use super::{receipt_pda, ProtobookAccount, ReceiptV1};
+
+/// A receipt tracks a deposit to fill an order.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+pub struct ReceiptV2 {
+    /// The holder of the receipt (order taker).
+    pub authority: Pubkey,
+
+    /// The amount of token B deposited by the taker.
+    pub deposit: u64,
+
+    /// The order this receipt is redeemable for.
+    pub order: Pubkey,
+
+    /// The lamports escrowed to reward whoever redeems the receipt.
+    pub tip: u64,
+
+    /// The bump of the receipt PDA.
+    pub bump: u8,
+
+    /// Unused.
+    pub padding: [u8; 7],
+}
+
+/// The current layout of a receipt account.
+#[deprecated(since = "0.1.5", note = "Use ReceiptV2")]
+pub type Receipt = ReceiptV2;
+
+impl ReceiptV2 {
+    /// Reads a receipt from account data of either layout.
+    /// Receipts in the original layout are upgraded in memory, with no tip and their bump left at zero.
+    pub fn try_from_any(data: &[u8]) -> Result<Self, ProgramError> {
+        match data.first().map(|d| ProtobookAccount::try_from(*d)) {
+            Some(Ok(ProtobookAccount::ReceiptV1)) => {
+                Ok(Self::from(*ReceiptV1::try_from_bytes(data)?))
+            }
+            _ => Ok(*Self::try_from_bytes(data)?),
+        }
+    }
+
+    /// Loads a receipt account of either layout.
+    /// Receipts in the original layout have no stored bump, so it is derived here until they are migrated.
+    pub fn load(receipt_info: &AccountInfo) -> Result<Self, ProgramError> {
+        receipt_info.has_owner(&crate::ID)?;
+        let data = receipt_info.try_borrow_data()?;
+        let mut receipt = Self::try_from_any(&data)?;
+        if data[0] == ReceiptV1::discriminator() {
+            receipt.bump = receipt_pda(receipt.authority, receipt.order).1;
+        }
+        Ok(receipt)
+    }
+
+    /// Writes the receipt back to its account, keeping the layout the account already has.
+    /// Receipts in the original layout have no room for a tip.
+    pub fn save(&self, receipt_info: &AccountInfo) -> ProgramResult {
+        receipt_info.is_writable()?;
+        let mut data = receipt_info.try_borrow_mut_data()?;
+        if data.first() == Some(&ReceiptV1::discriminator()) {
+            if self.tip > 0 {
+                return Err(ProgramError::InvalidAccountData);
+            }
+            ReceiptV1::try_from_bytes_mut(&mut data)?.deposit = self.deposit;
+        } else {
+            *Self::try_from_bytes_mut(&mut data)? = *self;
+        }
+        Ok(())
+    }
+
+    /// Returns the account data of a receipt in the original layout, upgraded to the current one.
+    /// The account must be resized to the length of the returned data before it is written.
+    pub fn migrate(data: &[u8], bump: u8) -> Result<Vec<u8>, ProgramError> {
+        let receipt = Self {
+            bump,
+            ..Self::from(*ReceiptV1::try_from_bytes(data)?)
+        };
+        let mut data = vec![0; 8 + std::mem::size_of::<Self>()];
+        data[0] = Self::discriminator();
+        data[8..].copy_from_slice(receipt.to_bytes());
+        Ok(data)
+    }
+}
+
+impl From<ReceiptV1> for ReceiptV2 {
+    fn from(receipt: ReceiptV1) -> Self {
+        Self {
+            authority: receipt.authority,
+            deposit: receipt.deposit,
+            order: receipt.order,
+            tip: 0,
+            bump: 0,
+            padding: [0; 7],
+        }
+    }
+}
+
+account!(ProtobookAccount, ReceiptV2);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_receipt_reads_either_layout() {
+        let legacy = ReceiptV1 {
+            authority: Pubkey::new_unique(),
+            deposit: 100,
+            order: Pubkey::new_unique(),
+        };
+        let mut data = vec![ReceiptV1::discriminator(), 0, 0, 0, 0, 0, 0, 0];
+        data.extend_from_slice(legacy.to_bytes());
+        let receipt = ReceiptV2::try_from_any(&data).unwrap();
+        assert_eq!(receipt.authority, legacy.authority);
+        assert_eq!(receipt.deposit, 100);
+        assert_eq!(receipt.order, legacy.order);
+        assert_eq!(receipt.tip, 0);
+
+        let current = ReceiptV2 { tip: 5, ..receipt };
+        let mut data = vec![ReceiptV2::discriminator(), 0, 0, 0, 0, 0, 0, 0];
+        data.extend_from_slice(current.to_bytes());
+        assert_eq!(ReceiptV2::try_from_any(&data).unwrap(), current);
+    }
+
+    #[test]
+    fn test_migrate_receipt_grows_to_current_layout() {
+        let legacy = ReceiptV1 {
+            authority: Pubkey::new_unique(),
+            deposit: 100,
+            order: Pubkey::new_unique(),
+        };
+        let mut data = vec![ReceiptV1::discriminator(), 0, 0, 0, 0, 0, 0, 0];
+        data.extend_from_slice(legacy.to_bytes());
+        let migrated = ReceiptV2::migrate(&data, 254).unwrap();
+        assert_eq!(migrated.len(), 8 + std::mem::size_of::<ReceiptV2>());
+        assert_eq!(migrated.len() - data.len(), 16);
+        assert_eq!(
+            *ReceiptV2::try_from_bytes(&migrated).unwrap(),
+            ReceiptV2 {
+                bump: 254,
+                ..ReceiptV2::from(legacy)
+            }
+        );
+
+        // Receipts already in the current layout cannot be migrated again.
+        assert!(ReceiptV2::migrate(&migrated, 254).is_err());
+    }
+}
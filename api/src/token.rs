@@ -0,0 +1,203 @@
+use solana_program::program_pack::Pack;
+use spl_token_2022::{
+    extension::{transfer_fee::TransferFeeConfig, BaseStateWithExtensions, StateWithExtensions},
+    state::Mint,
+};
+use steel::*;
+
+use crate::{consts::*, state::unwrap_pda};
+
+/// Returns true if the given program is a supported token program.
+pub fn is_token_program(program_id: &Pubkey) -> bool {
+    *program_id == spl_token::ID || *program_id == spl_token_2022::ID
+}
+
+/// Returns the decimals of a mint owned by either token program.
+pub fn mint_decimals(mint_info: &AccountInfo) -> Result<u8, ProgramError> {
+    if !is_token_program(mint_info.owner) {
+        return Err(ProgramError::InvalidAccountOwner);
+    }
+    let data = mint_info.try_borrow_data()?;
+    let mint = StateWithExtensions::<Mint>::unpack(&data)?;
+    Ok(mint.base.decimals)
+}
+
+/// Returns the amount a sender must transfer for the recipient to receive the given amount.
+/// This only differs from the given amount for mints with a transfer fee.
+pub fn pre_fee_amount(
+    mint_info: &AccountInfo,
+    epoch: u64,
+    amount: u64,
+) -> Result<u64, ProgramError> {
+    let data = mint_info.try_borrow_data()?;
+    let mint = StateWithExtensions::<Mint>::unpack(&data)?;
+    match mint.get_extension::<TransferFeeConfig>() {
+        Ok(config) => config
+            .get_epoch_fee(epoch)
+            .calculate_pre_fee_amount(amount)
+            .ok_or(ProgramError::ArithmeticOverflow),
+        Err(_) => Ok(amount),
+    }
+}
+
+/// Moves the transfer fees withheld in a token account to its mint, so the account can be closed.
+/// This is a no-op for mints without a transfer fee.
+pub fn harvest_withheld_tokens<'info>(
+    mint_info: &AccountInfo<'info>,
+    token_account_info: &AccountInfo<'info>,
+    token_program: &AccountInfo<'info>,
+) -> ProgramResult {
+    let has_transfer_fee = {
+        let data = mint_info.try_borrow_data()?;
+        let mint = StateWithExtensions::<Mint>::unpack(&data)?;
+        mint.get_extension::<TransferFeeConfig>().is_ok()
+    };
+    if !has_transfer_fee {
+        return Ok(());
+    }
+    solana_program::program::invoke(
+        &spl_token_2022::extension::transfer_fee::instruction::harvest_withheld_tokens_to_mint(
+            token_program.key,
+            mint_info.key,
+            &[token_account_info.key],
+        )?,
+        &[
+            mint_info.clone(),
+            token_account_info.clone(),
+            token_program.clone(),
+        ],
+    )
+}
+
+/// Returns true if the given mint is the native SOL mint.
+pub fn is_native_mint(mint: &Pubkey) -> bool {
+    *mint == spl_token::native_mint::ID
+}
+
+/// Deposits lamports into a native SOL vault as wrapped SOL.
+pub fn wrap<'info>(
+    payer_info: &AccountInfo<'info>,
+    vault_info: &AccountInfo<'info>,
+    amount: u64,
+) -> ProgramResult {
+    vault_info.collect(amount, payer_info)?;
+    sync_native(vault_info)
+}
+
+/// Withdraws wrapped SOL from an order's vault to a recipient as lamports.
+/// The tokens pass through a temporary account which is closed back into the order,
+/// so the payer is refunded its rent and the recipient receives exactly the amount.
+#[allow(clippy::too_many_arguments)]
+pub fn unwrap_signed<'info>(
+    order_info: &AccountInfo<'info>,
+    vault_info: &AccountInfo<'info>,
+    mint_info: &AccountInfo<'info>,
+    unwrap_info: &AccountInfo<'info>,
+    recipient_info: &AccountInfo<'info>,
+    payer_info: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    token_program: &AccountInfo<'info>,
+    amount: u64,
+    seeds: &[&[u8]],
+    bump: u8,
+) -> ProgramResult {
+    // Create the temporary account.
+    let (unwrap_address, unwrap_bump) = unwrap_pda(*order_info.key);
+    unwrap_info.is_writable()?.has_address(&unwrap_address)?;
+    allocate_account_with_bump(
+        unwrap_info,
+        system_program,
+        payer_info,
+        spl_token::state::Account::LEN,
+        token_program.key,
+        &[UNWRAP, order_info.key.as_ref()],
+        unwrap_bump,
+    )?;
+    solana_program::program::invoke(
+        &spl_token_2022::instruction::initialize_account3(
+            token_program.key,
+            unwrap_info.key,
+            mint_info.key,
+            order_info.key,
+        )?,
+        &[
+            unwrap_info.clone(),
+            mint_info.clone(),
+            token_program.clone(),
+        ],
+    )?;
+
+    // Move the tokens and close the temporary account into the order.
+    transfer_checked_signed_with_bump(
+        order_info,
+        vault_info,
+        mint_info,
+        unwrap_info,
+        token_program,
+        amount,
+        spl_token::native_mint::DECIMALS,
+        seeds,
+        bump,
+    )?;
+    let rent = unwrap_info.lamports() - amount;
+    close_token_account_signed_with_bump(
+        unwrap_info,
+        order_info,
+        order_info,
+        token_program,
+        seeds,
+        bump,
+    )?;
+
+    // Pay out the lamports and refund the rent.
+    order_info.send(amount, recipient_info);
+    order_info.send(rent, payer_info);
+
+    Ok(())
+}
+
+/// Pays a protocol fee from an order's vault to the fee collector.
+/// The collector's token account is created if necessary.
+#[allow(clippy::too_many_arguments)]
+pub fn pay_fee_signed<'info>(
+    order_info: &AccountInfo<'info>,
+    vault_info: &AccountInfo<'info>,
+    mint_info: &AccountInfo<'info>,
+    fee_info: &AccountInfo<'info>,
+    fee_collector_info: &AccountInfo<'info>,
+    payer_info: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    token_program: &AccountInfo<'info>,
+    associated_token_program: &AccountInfo<'info>,
+    amount: u64,
+    seeds: &[&[u8]],
+    bump: u8,
+) -> ProgramResult {
+    if amount == 0 {
+        return Ok(());
+    }
+    if fee_info.data_is_empty() {
+        create_associated_token_account(
+            payer_info,
+            fee_collector_info,
+            fee_info,
+            mint_info,
+            system_program,
+            token_program,
+            associated_token_program,
+        )?;
+    } else {
+        fee_info.as_associated_token_account(fee_collector_info.key, mint_info.key)?;
+    }
+    transfer_checked_signed_with_bump(
+        order_info,
+        vault_info,
+        mint_info,
+        fee_info,
+        token_program,
+        amount,
+        mint_decimals(mint_info)?,
+        seeds,
+        bump,
+    )
+}
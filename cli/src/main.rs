@@ -16,14 +16,13 @@ use solana_sdk::{
     signature::{read_keypair_file, Signature, Signer},
     transaction::{Transaction, VersionedTransaction},
 };
-use spl_associated_token_account::get_associated_token_address;
 use steel::{AccountDeserialize, Clock, Discriminator, Instruction};
 
 #[tokio::main]
 async fn main() {
     // Read keypair from file
     let payer =
-        read_keypair_file(&std::env::var("KEYPAIR").expect("Missing KEYPAIR env var")).unwrap();
+        read_keypair_file(std::env::var("KEYPAIR").expect("Missing KEYPAIR env var")).unwrap();
 
     // Build transaction
     let rpc = RpcClient::new(std::env::var("RPC").expect("Missing RPC env var"));
@@ -43,18 +42,48 @@ async fn main() {
         "receipt" => {
             log_receipt(&rpc).await.unwrap();
         }
+        "amend" => {
+            amend(&rpc, &payer).await.unwrap();
+        }
         "cancel" => {
             cancel(&rpc, &payer).await.unwrap();
         }
+        "cancel_all" => {
+            cancel_all(&rpc, &payer).await.unwrap();
+        }
         "collect" => {
             collect(&rpc, &payer).await.unwrap();
         }
+        "replace" => {
+            replace(&rpc, &payer).await.unwrap();
+        }
         "redeem" => {
             redeem(&rpc, &payer).await.unwrap();
         }
+        "transfer_receipt" => {
+            transfer_receipt(&rpc, &payer).await.unwrap();
+        }
         "close" => {
             close(&rpc, &payer).await.unwrap();
         }
+        "initialize_config" => {
+            initialize_config(&rpc, &payer).await.unwrap();
+        }
+        "update_config" => {
+            update_config(&rpc, &payer).await.unwrap();
+        }
+        "config" => {
+            log_config(&rpc).await.unwrap();
+        }
+        "init_market" => {
+            init_market(&rpc, &payer).await.unwrap();
+        }
+        "market" => {
+            log_market(&rpc).await.unwrap();
+        }
+        "migrate" => {
+            migrate(&rpc, &payer).await.unwrap();
+        }
         "order" => {
             log_order(&rpc).await.unwrap();
         }
@@ -91,8 +120,17 @@ async fn open(
     rpc: &RpcClient,
     payer: &solana_sdk::signer::keypair::Keypair,
 ) -> Result<(), anyhow::Error> {
-    let id = std::env::var("ID").unwrap();
-    let id = u64::from_str(&id).expect("Invalid ID");
+    // Without an ID, the program assigns the next id from the payer's counter LANE.
+    let lane = std::env::var("LANE")
+        .map(|v| u16::from_str(&v).expect("Invalid LANE"))
+        .unwrap_or(0);
+    let (id, lane) = match std::env::var("ID") {
+        Ok(id) => (u64::from_str(&id).expect("Invalid ID"), None),
+        Err(_) => {
+            let counter = get_counter(rpc, payer.pubkey(), lane).await.ok();
+            (Counter::next_id_of(counter.as_ref(), lane), Some(lane))
+        }
+    };
     let amount_a = std::env::var("AMOUNT_A").unwrap();
     let amount_a = u64::from_str(&amount_a).expect("Invalid AMOUNT_A");
     let amount_b = std::env::var("AMOUNT_B").unwrap();
@@ -101,18 +139,58 @@ async fn open(
     let mint_a = Pubkey::from_str(&mint_a).expect("Invalid MINT_A");
     let mint_b = std::env::var("MINT_B").unwrap();
     let mint_b = Pubkey::from_str(&mint_b).expect("Invalid MINT_B");
+    let is_atomic = std::env::var("ATOMIC").is_ok_and(|v| v == "true");
+    let is_listed = std::env::var("LISTED").is_ok_and(|v| v == "true");
+    let is_pro_rata = std::env::var("PRO_RATA").is_ok_and(|v| v == "true");
+    let is_tokenized = std::env::var("TOKENIZED").is_ok_and(|v| v == "true");
+    let lot_size = std::env::var("LOT_SIZE")
+        .map(|v| u64::from_str(&v).expect("Invalid LOT_SIZE"))
+        .unwrap_or(0);
+    let min_fill = std::env::var("MIN_FILL")
+        .map(|v| u64::from_str(&v).expect("Invalid MIN_FILL"))
+        .unwrap_or(0);
+    let tip = std::env::var("TIP")
+        .map(|v| u64::from_str(&v).expect("Invalid TIP"))
+        .unwrap_or(0);
+    let taker = std::env::var("TAKER")
+        .ok()
+        .map(|v| Pubkey::from_str(&v).expect("Invalid TAKER"));
+    let merkle_root = std::env::var("ALLOWLIST")
+        .ok()
+        .map(|path| merkle_root(&read_allowlist(&path)));
     let clock = get_clock(rpc).await?;
-    let expires_at = clock.unix_timestamp + (2 * 60 * 60); // 2 hours
+    let starts_at = std::env::var("STARTS_AT")
+        .map(|v| i64::from_str(&v).expect("Invalid STARTS_AT"))
+        .unwrap_or(clock.unix_timestamp);
+    let expires_at = starts_at.max(clock.unix_timestamp) + (2 * 60 * 60); // 2 hours
+    let token_program_a = get_token_program(rpc, mint_a).await?;
+    let token_program_b = get_token_program(rpc, mint_b).await?;
     let ix = protobook_api::sdk::open(
         payer.pubkey(),
         amount_a,
         amount_b,
         expires_at,
         id,
+        is_atomic,
+        is_listed,
+        is_pro_rata,
+        is_tokenized,
+        lane,
+        lot_size,
+        min_fill,
         mint_a,
         mint_b,
+        starts_at,
+        tip,
+        token_program_a,
+        token_program_b,
+        merkle_root,
+        taker,
     );
     submit_transaction(rpc, payer, &[ix]).await?;
+    println!("Order opened");
+    println!("  Id: {}", id);
+    println!("  Address: {}", order_pda(payer.pubkey(), id).0);
     Ok(())
 }
 
@@ -124,24 +202,191 @@ async fn fill(
     let order_address = Pubkey::from_str(&order_address).expect("Invalid ORDER_ADDRESS");
     let amount = std::env::var("AMOUNT").unwrap();
     let amount = u64::from_str(&amount).expect("Invalid AMOUNT");
-    let order = get_order(&rpc, order_address).await?;
+    let tip = std::env::var("TIP")
+        .map(|v| u64::from_str(&v).expect("Invalid TIP"))
+        .unwrap_or(0);
+    let referrer = std::env::var("REFERRER")
+        .ok()
+        .map(|v| Pubkey::from_str(&v).expect("Invalid REFERRER"));
+    let referral_fee = std::env::var("REFERRAL_FEE")
+        .map(|v| u64::from_str(&v).expect("Invalid REFERRAL_FEE"))
+        .unwrap_or(0);
+    if referral_fee > MAX_REFERRAL_FEE {
+        return Err(anyhow::anyhow!(
+            "Referral fee must be at most {} bps",
+            MAX_REFERRAL_FEE
+        ));
+    }
+    let order = get_order(rpc, order_address).await?;
     let clock = get_clock(rpc).await?;
     if order.expires_at < clock.unix_timestamp {
         return Err(anyhow::anyhow!("Order expired"));
     }
+    if order.starts_at > clock.unix_timestamp {
+        return Err(anyhow::anyhow!("Order has not started"));
+    }
     if order.amount_b <= order.total_deposits {
         return Err(anyhow::anyhow!("Order is filled"));
     }
-    let ix = protobook_api::sdk::fill(payer.pubkey(), order_address, order.mint_b, amount);
+    let (deposit, _) = split_referral(amount, referral_fee, order.amount_b - order.total_deposits);
+    if !order.is_valid_fill(deposit) {
+        return Err(anyhow::anyhow!(
+            "Fill must be at least {} and a multiple of {}",
+            order.min_fill,
+            order.lot_size
+        ));
+    }
+    if get_maker_nonce(rpc, order.authority).await? != order.nonce {
+        return Err(anyhow::anyhow!("Order is cancelled"));
+    }
+    if !order.is_taker(&payer.pubkey()) {
+        return Err(anyhow::anyhow!("Order is reserved for another taker"));
+    }
+    let proof = match std::env::var("ALLOWLIST") {
+        Ok(path) => {
+            let leaves = read_allowlist(&path);
+            let leaf = hash_leaf(&payer.pubkey());
+            let Some(index) = leaves.iter().position(|l| *l == leaf) else {
+                return Err(anyhow::anyhow!("Taker is not on the allowlist"));
+            };
+            merkle_proof(&leaves, index)
+        }
+        Err(_) => vec![],
+    };
+    let token_program_a = get_token_program(rpc, order.mint_a).await?;
+    let token_program_b = get_token_program(rpc, order.mint_b).await?;
+    let fee_collector = get_fee_collector(rpc).await?;
+    let ix = protobook_api::sdk::fill_with_proof(
+        payer.pubkey(),
+        order.authority,
+        order_address,
+        order.mint_a,
+        order.mint_b,
+        token_program_a,
+        token_program_b,
+        fee_collector,
+        amount,
+        tip,
+        referrer,
+        referral_fee,
+        &proof,
+    );
     submit_transaction(rpc, payer, &[ix]).await?;
+    if order.is_atomic() || order.is_tokenized() {
+        println!("Order filled");
+        return Ok(());
+    }
 
     // Log receipt
     let receipt_address = receipt_pda(payer.pubkey(), order_address).0;
-    let receipt = get_receipt(&rpc, receipt_address).await?;
+    let receipt = get_receipt(rpc, receipt_address).await?;
     print_receipt(receipt).await?;
     Ok(())
 }
 
+async fn amend(
+    rpc: &RpcClient,
+    payer: &solana_sdk::signer::keypair::Keypair,
+) -> Result<(), anyhow::Error> {
+    let id = std::env::var("ID").unwrap();
+    let id = u64::from_str(&id).expect("Invalid ID");
+    let order_address = order_pda(payer.pubkey(), id).0;
+    let order = get_order(rpc, order_address).await?;
+    let amount_a = std::env::var("AMOUNT_A")
+        .map(|v| u64::from_str(&v).expect("Invalid AMOUNT_A"))
+        .unwrap_or(order.amount_a);
+    let amount_b = std::env::var("AMOUNT_B")
+        .map(|v| u64::from_str(&v).expect("Invalid AMOUNT_B"))
+        .unwrap_or(order.amount_b);
+    let expires_at = std::env::var("EXPIRES_AT")
+        .map(|v| i64::from_str(&v).expect("Invalid EXPIRES_AT"))
+        .unwrap_or(order.expires_at);
+    let token_program_a = get_token_program(rpc, order.mint_a).await?;
+    let ix = protobook_api::sdk::amend(
+        payer.pubkey(),
+        order_address,
+        order.mint_a,
+        order.mint_b,
+        token_program_a,
+        amount_a,
+        amount_b,
+        expires_at,
+    );
+    submit_transaction(rpc, payer, &[ix]).await?;
+    println!("Order amended");
+    Ok(())
+}
+
+async fn cancel_all(
+    rpc: &RpcClient,
+    payer: &solana_sdk::signer::keypair::Keypair,
+) -> Result<(), anyhow::Error> {
+    let ix = protobook_api::sdk::cancel_all(payer.pubkey());
+    submit_transaction(rpc, payer, &[ix]).await?;
+    println!("All orders cancelled");
+    Ok(())
+}
+
+async fn transfer_receipt(
+    rpc: &RpcClient,
+    payer: &solana_sdk::signer::keypair::Keypair,
+) -> Result<(), anyhow::Error> {
+    let order_address = std::env::var("ORDER").unwrap();
+    let order_address = Pubkey::from_str(&order_address).expect("Invalid ORDER");
+    let new_authority = std::env::var("NEW_AUTHORITY").unwrap();
+    let new_authority = Pubkey::from_str(&new_authority).expect("Invalid NEW_AUTHORITY");
+    let receipt_address = receipt_pda(payer.pubkey(), order_address).0;
+    let receipt = get_receipt(rpc, receipt_address).await?;
+    if receipt.order != order_address {
+        return Err(anyhow::anyhow!("Receipt does not match order"));
+    }
+    let ix = protobook_api::sdk::transfer_receipt(payer.pubkey(), new_authority, order_address);
+    submit_transaction(rpc, payer, &[ix]).await?;
+    println!("Receipt transferred");
+    Ok(())
+}
+
+async fn replace(
+    rpc: &RpcClient,
+    payer: &solana_sdk::signer::keypair::Keypair,
+) -> Result<(), anyhow::Error> {
+    let id = std::env::var("ID").unwrap();
+    let id = u64::from_str(&id).expect("Invalid ID");
+    let new_id = std::env::var("NEW_ID").unwrap();
+    let new_id = u64::from_str(&new_id).expect("Invalid NEW_ID");
+    let order_address = order_pda(payer.pubkey(), id).0;
+    let order = get_order(rpc, order_address).await?;
+    let amount_a = std::env::var("AMOUNT_A")
+        .map(|v| u64::from_str(&v).expect("Invalid AMOUNT_A"))
+        .unwrap_or(order.amount_a);
+    let amount_b = std::env::var("AMOUNT_B")
+        .map(|v| u64::from_str(&v).expect("Invalid AMOUNT_B"))
+        .unwrap_or(order.amount_b);
+    let expires_at = std::env::var("EXPIRES_AT")
+        .map(|v| i64::from_str(&v).expect("Invalid EXPIRES_AT"))
+        .unwrap_or(order.expires_at);
+    let token_program_a = get_token_program(rpc, order.mint_a).await?;
+    let token_program_b = get_token_program(rpc, order.mint_b).await?;
+    let fee_collector = get_fee_collector(rpc).await?;
+    let ix = protobook_api::sdk::replace(
+        payer.pubkey(),
+        order_address,
+        amount_a,
+        amount_b,
+        expires_at,
+        new_id,
+        order.mint_a,
+        order.mint_b,
+        token_program_a,
+        token_program_b,
+        fee_collector,
+        order.is_listed(),
+    );
+    submit_transaction(rpc, payer, &[ix]).await?;
+    println!("Order replaced");
+    Ok(())
+}
+
 async fn cancel(
     rpc: &RpcClient,
     payer: &solana_sdk::signer::keypair::Keypair,
@@ -162,22 +407,37 @@ async fn collect(
 ) -> Result<(), anyhow::Error> {
     let id = std::env::var("ID").unwrap();
     let id = u64::from_str(&id).expect("Invalid ID");
+    let authority = std::env::var("AUTHORITY")
+        .map(|v| Pubkey::from_str(&v).expect("Invalid AUTHORITY"))
+        .unwrap_or(payer.pubkey());
+    let owner = std::env::var("OWNER")
+        .map(|v| Pubkey::from_str(&v).expect("Invalid OWNER"))
+        .unwrap_or(authority);
     let clock = get_clock(rpc).await?;
-    let order_address = order_pda(payer.pubkey(), id).0;
-    let order = get_order(&rpc, order_address).await?;
-    if order.is_collected == 1 {
+    let order_address = order_pda(authority, id).0;
+    let order = get_order(rpc, order_address).await?;
+    if order.is_collected() {
         return Err(anyhow::anyhow!("Order is collected"));
     }
-    if order.expires_at > clock.unix_timestamp {
+    let maker_nonce = get_maker_nonce(rpc, order.authority).await?;
+    if !order.is_expired(clock.unix_timestamp, maker_nonce) {
         return Err(anyhow::anyhow!("Order is open"));
     }
-    let mint = if order.total_deposits == order.amount_b {
-        order.mint_b
-    } else {
-        order.mint_a
-    };
-    let beneficiary = get_associated_token_address(&order_address, &mint);
-    let ix = protobook_api::sdk::collect(payer.pubkey(), beneficiary, order_address, mint);
+    let token_program_a = get_token_program(rpc, order.mint_a).await?;
+    let token_program_b = get_token_program(rpc, order.mint_b).await?;
+    let fee_collector = get_fee_collector(rpc).await?;
+    let ix = protobook_api::sdk::collect(
+        payer.pubkey(),
+        authority,
+        owner,
+        order_address,
+        order.mint_a,
+        order.mint_b,
+        token_program_a,
+        token_program_b,
+        fee_collector,
+        order.is_listed(),
+    );
     submit_transaction(rpc, payer, &[ix]).await?;
     println!("Order collected");
     Ok(())
@@ -193,20 +453,37 @@ async fn redeem(
     let id = u64::from_str(&id).expect("Invalid ID");
     let clock = get_clock(rpc).await?;
     let order_address = order_pda(payer.pubkey(), id).0;
-    let order = get_order(&rpc, order_address).await?;
+    let order = get_order(rpc, order_address).await?;
     if order.total_redeemed == order.total_receipts {
         return Err(anyhow::anyhow!("Order is redeemed"));
     }
-    if order.expires_at > clock.unix_timestamp {
+    let maker_nonce = get_maker_nonce(rpc, order.authority).await?;
+    if !order.is_expired(clock.unix_timestamp, maker_nonce) {
         return Err(anyhow::anyhow!("Order is open"));
     }
-    let mint = if order.total_deposits == order.amount_b {
+    let mint = if order.is_swapped() {
         order.mint_a
     } else {
         order.mint_b
     };
-    let beneficiary = get_associated_token_address(&order_address, &mint);
-    let ix = protobook_api::sdk::redeem(payer.pubkey(), beneficiary, order_address, mint);
+    let authority = std::env::var("AUTHORITY")
+        .map(|v| Pubkey::from_str(&v).expect("Invalid AUTHORITY"))
+        .unwrap_or(payer.pubkey());
+    let owner = std::env::var("OWNER")
+        .map(|v| Pubkey::from_str(&v).expect("Invalid OWNER"))
+        .unwrap_or(authority);
+    let token_program = get_token_program(rpc, mint).await?;
+    let fee_collector = get_fee_collector(rpc).await?;
+    let ix = protobook_api::sdk::redeem(
+        payer.pubkey(),
+        authority,
+        owner,
+        order.authority,
+        order_address,
+        mint,
+        token_program,
+        fee_collector,
+    );
     submit_transaction(rpc, payer, &[ix]).await?;
     println!("Receipt redeemed");
     Ok(())
@@ -219,26 +496,161 @@ async fn close(
 ) -> Result<(), anyhow::Error> {
     let id = std::env::var("ID").unwrap();
     let id = u64::from_str(&id).expect("Invalid ID");
+    let authority = std::env::var("AUTHORITY")
+        .map(|v| Pubkey::from_str(&v).expect("Invalid AUTHORITY"))
+        .unwrap_or(payer.pubkey());
+    let owner = std::env::var("OWNER")
+        .map(|v| Pubkey::from_str(&v).expect("Invalid OWNER"))
+        .unwrap_or(authority);
     let clock = get_clock(rpc).await?;
-    let order_address = order_pda(payer.pubkey(), id).0;
-    let order = get_order(&rpc, order_address).await?;
+    let order_address = order_pda(authority, id).0;
+    let order = get_order(rpc, order_address).await?;
     if order.expires_at > clock.unix_timestamp {
         return Err(anyhow::anyhow!("Order is open"));
     }
+    if authority != payer.pubkey() && order.expires_at + CLOSE_GRACE_PERIOD >= clock.unix_timestamp
+    {
+        return Err(anyhow::anyhow!("Order is in grace period"));
+    }
     if order.total_receipts != order.total_redeemed {
         return Err(anyhow::anyhow!("Order is not redeemed"));
     }
-    if order.is_collected == 0 {
+    if !order.is_collected() {
         return Err(anyhow::anyhow!("Order is not collected"));
     }
-    let ix = protobook_api::sdk::close(payer.pubkey(), order_address, order.mint_a, order.mint_b);
+    let token_program_a = get_token_program(rpc, order.mint_a).await?;
+    let token_program_b = get_token_program(rpc, order.mint_b).await?;
+    let ix = protobook_api::sdk::close(
+        payer.pubkey(),
+        authority,
+        owner,
+        order_address,
+        order.mint_a,
+        order.mint_b,
+        token_program_a,
+        token_program_b,
+    );
     submit_transaction(rpc, payer, &[ix]).await?;
     println!("Order closed");
     Ok(())
 }
 
+async fn migrate(
+    rpc: &RpcClient,
+    payer: &solana_sdk::signer::keypair::Keypair,
+) -> Result<(), anyhow::Error> {
+    let address = std::env::var("ACCOUNT").unwrap();
+    let address = Pubkey::from_str(&address).expect("Invalid ACCOUNT");
+    let account = rpc.get_account(&address).await?;
+    let discriminator = account.data.first().copied();
+    if discriminator != Some(OrderV1::discriminator())
+        && discriminator != Some(ReceiptV1::discriminator())
+    {
+        return Err(anyhow::anyhow!("Account is migrated"));
+    }
+    let ix = protobook_api::sdk::migrate(payer.pubkey(), address);
+    submit_transaction(rpc, payer, &[ix]).await?;
+    println!("Account migrated");
+    Ok(())
+}
+
+async fn init_market(
+    rpc: &RpcClient,
+    payer: &solana_sdk::signer::keypair::Keypair,
+) -> Result<(), anyhow::Error> {
+    let mint_a = std::env::var("MINT_A").unwrap();
+    let mint_a = Pubkey::from_str(&mint_a).expect("Invalid MINT_A");
+    let mint_b = std::env::var("MINT_B").unwrap();
+    let mint_b = Pubkey::from_str(&mint_b).expect("Invalid MINT_B");
+    let lot_size = std::env::var("LOT_SIZE").unwrap();
+    let lot_size = u64::from_str(&lot_size).expect("Invalid LOT_SIZE");
+    let min_order_size = std::env::var("MIN_ORDER_SIZE")
+        .map(|v| u64::from_str(&v).expect("Invalid MIN_ORDER_SIZE"))
+        .unwrap_or(lot_size);
+    let tick_size = std::env::var("TICK_SIZE").unwrap();
+    let tick_size = u64::from_str(&tick_size).expect("Invalid TICK_SIZE");
+    let ix = protobook_api::sdk::init_market(
+        payer.pubkey(),
+        mint_a,
+        mint_b,
+        lot_size,
+        min_order_size,
+        tick_size,
+    );
+    submit_transaction(rpc, payer, &[ix]).await?;
+    println!("Market initialized");
+    println!("  Address: {}", market_pda(mint_a, mint_b).0);
+    Ok(())
+}
+
+async fn initialize_config(
+    rpc: &RpcClient,
+    payer: &solana_sdk::signer::keypair::Keypair,
+) -> Result<(), anyhow::Error> {
+    let fee = std::env::var("FEE").unwrap();
+    let fee = u64::from_str(&fee).expect("Invalid FEE");
+    let fee_collector = std::env::var("FEE_COLLECTOR")
+        .map(|v| Pubkey::from_str(&v).expect("Invalid FEE_COLLECTOR"))
+        .unwrap_or(payer.pubkey());
+    let ix = protobook_api::sdk::initialize_config(payer.pubkey(), fee, fee_collector);
+    submit_transaction(rpc, payer, &[ix]).await?;
+    println!("Config initialized");
+    Ok(())
+}
+
+async fn update_config(
+    rpc: &RpcClient,
+    payer: &solana_sdk::signer::keypair::Keypair,
+) -> Result<(), anyhow::Error> {
+    let config = get_config(rpc).await?;
+    let admin = std::env::var("ADMIN")
+        .map(|v| Pubkey::from_str(&v).expect("Invalid ADMIN"))
+        .unwrap_or(config.admin);
+    let fee = std::env::var("FEE")
+        .map(|v| u64::from_str(&v).expect("Invalid FEE"))
+        .unwrap_or(config.fee);
+    let fee_collector = std::env::var("FEE_COLLECTOR")
+        .map(|v| Pubkey::from_str(&v).expect("Invalid FEE_COLLECTOR"))
+        .unwrap_or(config.fee_collector);
+    let is_paused = std::env::var("PAUSED")
+        .map(|v| v == "true")
+        .unwrap_or(config.is_paused == 1);
+    let ix =
+        protobook_api::sdk::update_config(payer.pubkey(), admin, fee, fee_collector, is_paused);
+    submit_transaction(rpc, payer, &[ix]).await?;
+    println!("Config updated");
+    Ok(())
+}
+
+async fn log_config(rpc: &RpcClient) -> Result<(), anyhow::Error> {
+    let config = get_config(rpc).await?;
+    println!("Admin: {}", config.admin);
+    println!("Fee: {} bps", config.fee);
+    println!("Fee collector: {}", config.fee_collector);
+    println!("Is paused: {}", config.is_paused == 1);
+    Ok(())
+}
+
+async fn log_market(rpc: &RpcClient) -> Result<(), anyhow::Error> {
+    let mint_a = std::env::var("MINT_A").unwrap();
+    let mint_a = Pubkey::from_str(&mint_a).expect("Invalid MINT_A");
+    let mint_b = std::env::var("MINT_B").unwrap();
+    let mint_b = Pubkey::from_str(&mint_b).expect("Invalid MINT_B");
+    let account = rpc.get_account(&market_pda(mint_a, mint_b).0).await?;
+    let market = Market::try_from_bytes(&account.data)?;
+    println!("Market");
+    println!("  Mint A: {}", market.mint_a);
+    println!("  Mint B: {}", market.mint_b);
+    println!("  Lot size: {}", market.lot_size);
+    println!("  Min order size: {}", market.min_order_size);
+    println!("  Tick size: {}", market.tick_size);
+    println!("  Live orders: {}", market.live_orders);
+    println!("  Total orders: {}", market.total_orders);
+    Ok(())
+}
+
 async fn log_clock(rpc: &RpcClient) -> Result<(), anyhow::Error> {
-    let clock = get_clock(&rpc).await?;
+    let clock = get_clock(rpc).await?;
     println!("Clock");
     println!("  slot: {}", clock.slot);
     println!("  epoch_start_timestamp: {}", clock.epoch_start_timestamp);
@@ -251,7 +663,7 @@ async fn log_clock(rpc: &RpcClient) -> Result<(), anyhow::Error> {
 async fn log_receipt(rpc: &RpcClient) -> Result<(), anyhow::Error> {
     let address = std::env::var("ADDRESS").unwrap();
     let address = Pubkey::from_str(&address).expect("Invalid ADDRESS");
-    let receipt = get_receipt(&rpc, address).await?;
+    let receipt = get_receipt(rpc, address).await?;
     print_receipt(receipt).await?;
     Ok(())
 }
@@ -259,31 +671,56 @@ async fn log_receipt(rpc: &RpcClient) -> Result<(), anyhow::Error> {
 async fn log_order(rpc: &RpcClient) -> Result<(), anyhow::Error> {
     let address = std::env::var("ADDRESS").unwrap();
     let address = Pubkey::from_str(&address).expect("Invalid ADDRESS");
-    let order = get_order(&rpc, address).await?;
-    let clock = get_clock(&rpc).await?;
+    let order = get_order(rpc, address).await?;
+    let clock = get_clock(rpc).await?;
     print_order(order, &clock);
     Ok(())
 }
 
-fn print_order(order: Order, clock: &Clock) {
+/// Reads an allowlist file of one taker pubkey per line into merkle leaves.
+fn read_allowlist(path: &str) -> Vec<[u8; 32]> {
+    std::fs::read_to_string(path)
+        .expect("Invalid ALLOWLIST")
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| hash_leaf(&Pubkey::from_str(line.trim()).expect("Invalid ALLOWLIST entry")))
+        .collect()
+}
+
+fn print_order(order: OrderV2, _clock: &Clock) {
     println!("Order");
+    println!("  Version: {}", order.version);
+    println!("  Status: {:?}", order.status());
     println!("  Id: {:?}", order.id);
     println!("  Amount A: {}", order.amount_a);
     println!("  Amount B: {}", order.amount_b);
+    println!("  Starts at: {}", order.starts_at);
     println!("  Expires at: {}", order.expires_at);
     println!("  Mint A: {}", order.mint_a);
     println!("  Mint B: {}", order.mint_b);
     println!("  Total deposits: {}", order.total_deposits);
     println!("  Total receipts: {}", order.total_receipts);
     println!("  Total redeemed: {}", order.total_redeemed);
-    println!("  Is collected: {}", order.is_collected);
+    println!("  Is pro rata: {}", order.is_pro_rata());
+    println!("  Is atomic: {}", order.is_atomic());
+    println!("  Taker: {}", order.taker);
+    println!("  Merkle root: {:?}", order.merkle_root);
+    println!("  Min fill: {}", order.min_fill);
+    println!("  Lot size: {}", order.lot_size);
+    println!("  Nonce: {}", order.nonce);
+    println!("  Tip: {}", order.tip);
+    println!("  Is tokenized: {}", order.is_tokenized());
+    if order.is_listed() {
+        println!("  Market: {}", order.market);
+    }
 }
 
-async fn print_receipt(receipt: Receipt) -> Result<(), anyhow::Error> {
+async fn print_receipt(receipt: ReceiptV2) -> Result<(), anyhow::Error> {
     println!("Receipt");
     println!("  Authority: {}", receipt.authority);
     println!("  Deposit: {}", receipt.deposit);
     println!("  Order: {}", receipt.order);
+    println!("  Tip: {}", receipt.tip);
     Ok(())
 }
 
@@ -293,29 +730,85 @@ async fn get_clock(rpc: &RpcClient) -> Result<Clock, anyhow::Error> {
     Ok(clock)
 }
 
-async fn get_order(rpc: &RpcClient, address: Pubkey) -> Result<Order, anyhow::Error> {
+async fn get_order(rpc: &RpcClient, address: Pubkey) -> Result<OrderV2, anyhow::Error> {
     let account = rpc.get_account(&address).await?;
-    let order = Order::try_from_bytes(&account.data)?;
-    Ok(*order)
+    let order = OrderV2::try_from_any(&account.data)?;
+    Ok(order)
+}
+
+async fn get_counter(
+    rpc: &RpcClient,
+    authority: Pubkey,
+    lane: u16,
+) -> Result<Counter, anyhow::Error> {
+    let account = rpc.get_account(&counter_pda(authority, lane).0).await?;
+    let counter = Counter::try_from_bytes(&account.data)?;
+    Ok(*counter)
 }
 
-async fn get_receipt(rpc: &RpcClient, address: Pubkey) -> Result<Receipt, anyhow::Error> {
+async fn get_config(rpc: &RpcClient) -> Result<Config, anyhow::Error> {
+    let account = rpc.get_account(&config_pda().0).await?;
+    let config = Config::try_from_bytes(&account.data)?;
+    Ok(*config)
+}
+
+async fn get_fee_collector(rpc: &RpcClient) -> Result<Pubkey, anyhow::Error> {
+    // Without a config, no fee is charged and the fee accounts are ignored.
+    match get_config(rpc).await {
+        Ok(config) => Ok(config.fee_collector),
+        Err(_) => Ok(Pubkey::default()),
+    }
+}
+
+async fn get_token_program(rpc: &RpcClient, mint: Pubkey) -> Result<Pubkey, anyhow::Error> {
+    let account = rpc.get_account(&mint).await?;
+    Ok(account.owner)
+}
+
+async fn get_maker_nonce(rpc: &RpcClient, authority: Pubkey) -> Result<u64, anyhow::Error> {
+    let address = maker_pda(authority).0;
+    let Some(account) = rpc
+        .get_account_with_commitment(&address, rpc.commitment())
+        .await?
+        .value
+    else {
+        return Ok(0);
+    };
+    let maker = Maker::try_from_bytes(&account.data)?;
+    Ok(maker.nonce)
+}
+
+async fn get_receipt(rpc: &RpcClient, address: Pubkey) -> Result<ReceiptV2, anyhow::Error> {
     let account = rpc.get_account(&address).await?;
-    let receipt = Receipt::try_from_bytes(&account.data)?;
-    Ok(*receipt)
+    let receipt = ReceiptV2::try_from_any(&account.data)?;
+    Ok(receipt)
 }
 
 async fn get_receipts(
     rpc: &RpcClient,
     order: Pubkey,
-) -> Result<Vec<(Pubkey, Receipt)>, anyhow::Error> {
+) -> Result<Vec<(Pubkey, ReceiptV2)>, anyhow::Error> {
     let filter = RpcFilterType::Memcmp(Memcmp::new_base58_encoded(48, &order.to_bytes()));
-    let receipts = get_program_accounts::<Receipt>(rpc, protobook_api::ID, vec![filter]).await?;
+    let mut receipts =
+        get_program_accounts::<ReceiptV2>(rpc, protobook_api::ID, vec![filter.clone()]).await?;
+    let legacy_receipts =
+        get_program_accounts::<ReceiptV1>(rpc, protobook_api::ID, vec![filter]).await?;
+    receipts.extend(
+        legacy_receipts
+            .into_iter()
+            .map(|(address, receipt)| (address, ReceiptV2::from(receipt))),
+    );
     Ok(receipts)
 }
 
-async fn get_orders(rpc: &RpcClient) -> Result<Vec<(Pubkey, Order)>, anyhow::Error> {
-    let orders = get_program_accounts::<Order>(rpc, protobook_api::ID, vec![]).await?;
+async fn get_orders(rpc: &RpcClient) -> Result<Vec<(Pubkey, OrderV2)>, anyhow::Error> {
+    let mut orders = get_program_accounts::<OrderV2>(rpc, protobook_api::ID, vec![]).await?;
+    let legacy_orders = get_program_accounts::<OrderV1>(rpc, protobook_api::ID, vec![]).await?;
+    orders.extend(
+        legacy_orders
+            .into_iter()
+            .map(|(address, order)| (address, OrderV2::from(order))),
+    );
     Ok(orders)
 }
 
@@ -509,9 +1002,9 @@ where
                             );
                     }
                 }
-                return Err(anyhow::anyhow!("Failed to get program accounts: {}", err));
+                Err(anyhow::anyhow!("Failed to get program accounts: {}", err))
             }
-            _ => return Err(anyhow::anyhow!("Failed to get program accounts: {}", err)),
+            _ => Err(anyhow::anyhow!("Failed to get program accounts: {}", err)),
         },
     }
 }
@@ -0,0 +1,130 @@
+use std::cmp::Ordering;
+
+use protobook_api::prelude::*;
+use steel::*;
+
+/// Amends the price, size, or expiry of an open order.
+pub fn process_amend(accounts: &[AccountInfo<'_>], data: &[u8]) -> ProgramResult {
+    // Parse args.
+    let clock = Clock::get()?;
+    let args = Amend::try_from_bytes(data)?;
+    let amount_a = u64::from_le_bytes(args.amount_a);
+    let amount_b = u64::from_le_bytes(args.amount_b);
+    let expires_at = i64::from_le_bytes(args.expires_at);
+    if amount_a == 0 || amount_b == 0 || expires_at <= clock.unix_timestamp {
+        return Err(ProtobookError::InvalidTerms.into());
+    }
+
+    // Load accounts.
+    let [signer_info, config_info, maker_info, market_info, mint_a_info, order_info, sender_info, vault_a_info, token_program_a] =
+        accounts
+    else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    signer_info.is_signer()?;
+    let maker_nonce = Maker::nonce_of(maker_info, signer_info.key)?;
+    let mut order = OrderV2::load(order_info)?;
+    order
+        .assert_mut_err(
+            |o| o.authority == *signer_info.key,
+            ProtobookError::NotAuthority.into(),
+        )?
+        .assert_mut_err(
+            |o| o.expires_at > clock.unix_timestamp,
+            ProtobookError::OrderExpired.into(),
+        )?
+        .assert_mut_err(
+            |o| o.nonce == maker_nonce,
+            ProtobookError::OrderCancelled.into(),
+        )?;
+    mint_a_info.has_address(&order.mint_a)?;
+    let decimals_a = mint_decimals(mint_a_info)?;
+    sender_info
+        .is_writable()?
+        .as_associated_token_account(signer_info.key, &order.mint_a)?;
+    vault_a_info
+        .is_writable()?
+        .as_associated_token_account(order_info.key, &order.mint_a)?;
+    token_program_a.is_program(mint_a_info.owner)?;
+
+    // Check the program is not paused.
+    Config::assert_unpaused(config_info)?;
+
+    // Validate the new terms against the order.
+    if amount_b < order.total_deposits
+        || amount_b < order.lot_size
+        || amount_b < order.min_fill
+        || expires_at <= order.starts_at
+    {
+        return Err(ProtobookError::InvalidTerms.into());
+    }
+
+    // Listed orders must stay on their market's price grid.
+    if order.is_listed() {
+        market_info
+            .has_address(&order.market)?
+            .as_account::<Market>(&protobook_api::ID)?
+            .assert_err(
+                |m| m.is_valid_order(amount_a, amount_b),
+                ProtobookError::OffMarketGrid.into(),
+            )?;
+    }
+
+    // Once takers have deposited, the terms can only change in their favor.
+    // The amount of token A is fixed, the price can only improve, and the expiry can only be brought forward.
+    if order.total_deposits > 0
+        && (amount_a != order.amount_a
+            || amount_b > order.amount_b
+            || expires_at > order.expires_at)
+    {
+        return Err(ProtobookError::UnfavorableAmendment.into());
+    }
+
+    // Top up or withdraw token A from escrow.
+    // Top ups are grossed up so the escrow receives the full difference, net of any transfer fee.
+    match amount_a.cmp(&order.amount_a) {
+        Ordering::Greater => transfer_checked(
+            signer_info,
+            sender_info,
+            mint_a_info,
+            vault_a_info,
+            token_program_a,
+            pre_fee_amount(mint_a_info, clock.epoch, amount_a - order.amount_a)?,
+            decimals_a,
+        )?,
+        Ordering::Less => transfer_checked_signed_with_bump(
+            order_info,
+            vault_a_info,
+            mint_a_info,
+            sender_info,
+            token_program_a,
+            order.amount_a - amount_a,
+            decimals_a,
+            &[ORDER, signer_info.key.as_ref(), &order.id.to_le_bytes()],
+            order.bump,
+        )?,
+        Ordering::Equal => {}
+    }
+
+    // Update the order.
+    order.amount_a = amount_a;
+    order.amount_b = amount_b;
+    order.expires_at = expires_at;
+
+    // If filled, expire the order immediately.
+    if order.total_deposits == order.amount_b {
+        order.expires_at = clock.unix_timestamp;
+    }
+    order.save(order_info)?;
+
+    // Emit the event.
+    OrderAmended {
+        order: *order_info.key,
+        amount_a: order.amount_a,
+        amount_b: order.amount_b,
+        expires_at: order.expires_at,
+    }
+    .log();
+
+    Ok(())
+}
@@ -9,13 +9,28 @@ pub fn process_cancel(accounts: &[AccountInfo<'_>], _data: &[u8]) -> ProgramResu
         return Err(ProgramError::NotEnoughAccountKeys);
     };
     signer_info.is_signer()?;
-    let order = order_info
-        .as_account_mut::<Order>(&protobook_api::ID)?
-        .assert_mut(|o| o.authority == *signer_info.key)?
-        .assert_mut(|o| o.expires_at > clock.unix_timestamp)?;
+    let mut order = OrderV2::load(order_info)?;
+    order
+        .assert_mut_err(
+            |o| o.authority == *signer_info.key,
+            ProtobookError::NotAuthority.into(),
+        )?
+        .assert_mut_err(
+            |o| o.expires_at > clock.unix_timestamp,
+            ProtobookError::OrderExpired.into(),
+        )?;
 
     // Marks the order as immediately expired.
     order.expires_at = clock.unix_timestamp;
+    order.status = OrderStatus::Cancelled.into();
+    order.save(order_info)?;
+
+    // Emit the event.
+    OrderCancelled {
+        order: *order_info.key,
+        authority: *signer_info.key,
+    }
+    .log();
 
     Ok(())
 }
@@ -0,0 +1,27 @@
+use protobook_api::prelude::*;
+use steel::*;
+
+/// Cancels all open orders of an authority by advancing its nonce.
+pub fn process_cancel_all(accounts: &[AccountInfo<'_>], _data: &[u8]) -> ProgramResult {
+    // Load accounts.
+    let [signer_info, maker_info, system_program] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    signer_info.is_signer()?;
+    system_program.is_program(&system_program::ID)?;
+
+    // Create maker account, if necessary.
+    let maker = Maker::load_or_create(maker_info, signer_info, system_program)?;
+
+    // Advance the nonce so open orders can no longer be filled.
+    maker.nonce += 1;
+
+    // Emit the event.
+    OrdersCancelled {
+        authority: *signer_info.key,
+        nonce: maker.nonce,
+    }
+    .log();
+
+    Ok(())
+}
@@ -2,102 +2,195 @@ use protobook_api::prelude::*;
 use steel::*;
 
 /// Closes an order.
+/// Once the grace period has passed, anyone may close a settled order, but dust and rent are always returned to the order authority.
+/// The authority may direct dust to a different owner by signing.
 pub fn process_close(accounts: &[AccountInfo<'_>], _data: &[u8]) -> ProgramResult {
     // Load accounts.
     let clock = Clock::get()?;
-    let [signer_info, beneficiary_a_info, beneficiary_b_info, mint_a_info, mint_b_info, order_info, vault_a_info, vault_b_info, system_program, token_program, associated_token_program] =
+    let [signer_info, authority_info, beneficiary_a_info, beneficiary_b_info, mint_a_info, mint_b_info, order_info, owner_info, receipt_mint_info, unwrap_info, vault_a_info, vault_b_info, system_program, token_program_a, token_program_b, receipt_token_program, associated_token_program] =
         accounts
     else {
         return Err(ProgramError::NotEnoughAccountKeys);
     };
     signer_info.is_signer()?;
-    mint_a_info.as_mint()?;
-    mint_b_info.as_mint()?;
-    let order = order_info
-        .as_account_mut::<Order>(&protobook_api::ID)?
-        .assert_mut(|o| o.authority == *signer_info.key)?
-        .assert_mut(|o| o.mint_a == *mint_a_info.key)?
-        .assert_mut(|o| o.mint_b == *mint_b_info.key)?
-        .assert_mut(|o| o.expires_at < clock.unix_timestamp)?
-        .assert_mut(|o| o.total_receipts == o.total_redeemed)?
-        .assert_mut(|o| o.is_collected == 1)?;
+    let decimals_a = mint_decimals(mint_a_info)?;
+    let decimals_b = mint_decimals(mint_b_info)?;
+    let order = OrderV2::load(order_info)?;
+    order
+        .assert_err(
+            |o| o.authority == *authority_info.key,
+            ProtobookError::NotAuthority.into(),
+        )?
+        .assert_err(
+            |o| o.mint_a == *mint_a_info.key,
+            ProtobookError::MintMismatch.into(),
+        )?
+        .assert_err(
+            |o| o.mint_b == *mint_b_info.key,
+            ProtobookError::MintMismatch.into(),
+        )?
+        .assert_err(
+            |o| o.expires_at < clock.unix_timestamp,
+            ProtobookError::OrderNotExpired.into(),
+        )?
+        .assert_err(
+            |o| o.total_receipts == o.total_redeemed,
+            ProtobookError::UnredeemedReceipts.into(),
+        )?
+        .assert_err(|o| o.is_collected(), ProtobookError::NotCollected.into())?;
+    authority_info.is_writable()?;
+    if signer_info.key != authority_info.key
+        && order.expires_at.saturating_add(CLOSE_GRACE_PERIOD) >= clock.unix_timestamp
+    {
+        return Err(ProtobookError::NotAuthority.into());
+    }
     let vault_a = vault_a_info
         .is_writable()?
-        .as_associated_token_account(&order_info.key, &order.mint_a)?;
+        .as_associated_token_account(order_info.key, &order.mint_a)?;
     let vault_b = vault_b_info
         .is_writable()?
-        .as_associated_token_account(&order_info.key, &order.mint_b)?;
+        .as_associated_token_account(order_info.key, &order.mint_b)?;
+    if signer_info.key != authority_info.key && owner_info.key != authority_info.key {
+        return Err(ProtobookError::NotAuthority.into());
+    }
     system_program.is_program(&system_program::ID)?;
-    token_program.is_program(&spl_token::ID)?;
+    token_program_a.is_program(mint_a_info.owner)?;
+    token_program_b.is_program(mint_b_info.owner)?;
+    receipt_token_program.is_program(&spl_token_2022::ID)?;
     associated_token_program.is_program(&spl_associated_token_account::ID)?;
 
-    // Transfer any remaining dust to the creator.
+    // Transfer any remaining dust to the owner.
     // Dust can only happen due to rounding amounts when redeeming receipts.
     if vault_a.amount() > 0 {
-        if beneficiary_a_info.data_is_empty() {
-            create_associated_token_account(
-                signer_info,
-                signer_info,
-                beneficiary_a_info,
+        if is_native_mint(mint_a_info.key) {
+            unwrap_signed(
+                order_info,
+                vault_a_info,
                 mint_a_info,
+                unwrap_info,
+                owner_info,
+                signer_info,
                 system_program,
-                token_program,
-                associated_token_program,
+                token_program_a,
+                vault_a.amount(),
+                &[ORDER, authority_info.key.as_ref(), &order.id.to_le_bytes()],
+                order.bump,
             )?;
         } else {
-            beneficiary_a_info.as_associated_token_account(&signer_info.key, &order.mint_a)?;
+            if beneficiary_a_info.data_is_empty() {
+                create_associated_token_account(
+                    signer_info,
+                    owner_info,
+                    beneficiary_a_info,
+                    mint_a_info,
+                    system_program,
+                    token_program_a,
+                    associated_token_program,
+                )?;
+            } else {
+                beneficiary_a_info.as_associated_token_account(owner_info.key, &order.mint_a)?;
+            }
+            transfer_checked_signed_with_bump(
+                order_info,
+                vault_a_info,
+                mint_a_info,
+                beneficiary_a_info,
+                token_program_a,
+                vault_a.amount(),
+                decimals_a,
+                &[ORDER, authority_info.key.as_ref(), &order.id.to_le_bytes()],
+                order.bump,
+            )?;
         }
-        transfer_signed(
-            &order_info,
-            &vault_a_info,
-            &beneficiary_a_info,
-            &token_program,
-            vault_a.amount(),
-            &[&ORDER, signer_info.key.as_ref(), &order.id.to_le_bytes()],
-        )?;
     }
     if vault_b.amount() > 0 {
-        if beneficiary_b_info.data_is_empty() {
-            create_associated_token_account(
-                signer_info,
-                signer_info,
-                beneficiary_b_info,
+        if is_native_mint(mint_b_info.key) {
+            unwrap_signed(
+                order_info,
+                vault_b_info,
                 mint_b_info,
+                unwrap_info,
+                owner_info,
+                signer_info,
                 system_program,
-                token_program,
-                associated_token_program,
+                token_program_b,
+                vault_b.amount(),
+                &[ORDER, authority_info.key.as_ref(), &order.id.to_le_bytes()],
+                order.bump,
             )?;
         } else {
-            beneficiary_b_info.as_associated_token_account(&signer_info.key, &order.mint_b)?;
+            if beneficiary_b_info.data_is_empty() {
+                create_associated_token_account(
+                    signer_info,
+                    owner_info,
+                    beneficiary_b_info,
+                    mint_b_info,
+                    system_program,
+                    token_program_b,
+                    associated_token_program,
+                )?;
+            } else {
+                beneficiary_b_info.as_associated_token_account(owner_info.key, &order.mint_b)?;
+            }
+            transfer_checked_signed_with_bump(
+                order_info,
+                vault_b_info,
+                mint_b_info,
+                beneficiary_b_info,
+                token_program_b,
+                vault_b.amount(),
+                decimals_b,
+                &[ORDER, authority_info.key.as_ref(), &order.id.to_le_bytes()],
+                order.bump,
+            )?;
         }
-        transfer_signed(
-            &order_info,
-            &vault_b_info,
-            &beneficiary_b_info,
-            &token_program,
-            vault_b.amount(),
-            &[&ORDER, signer_info.key.as_ref(), &order.id.to_le_bytes()],
-        )?;
     }
 
-    // Close the escrow vaults.
-    close_token_account_signed(
-        &vault_a_info,
-        &signer_info,
-        &order_info,
-        &token_program,
-        &[&ORDER, signer_info.key.as_ref(), &order.id.to_le_bytes()],
+    // Close the escrow vaults, after moving any withheld transfer fees to the mints.
+    harvest_withheld_tokens(mint_a_info, vault_a_info, token_program_a)?;
+    harvest_withheld_tokens(mint_b_info, vault_b_info, token_program_b)?;
+    close_token_account_signed_with_bump(
+        vault_a_info,
+        authority_info,
+        order_info,
+        token_program_a,
+        &[ORDER, authority_info.key.as_ref(), &order.id.to_le_bytes()],
+        order.bump,
     )?;
-    close_token_account_signed(
-        &vault_b_info,
-        &signer_info,
-        &order_info,
-        &token_program,
-        &[&ORDER, signer_info.key.as_ref(), &order.id.to_le_bytes()],
+    close_token_account_signed_with_bump(
+        vault_b_info,
+        authority_info,
+        order_info,
+        token_program_b,
+        &[ORDER, authority_info.key.as_ref(), &order.id.to_le_bytes()],
+        order.bump,
     )?;
 
+    // Close the receipt mint of a tokenized order.
+    // Every receipt token has been burned by now, so the supply is zero.
+    if order.is_tokenized() {
+        receipt_mint_info
+            .is_writable()?
+            .has_address(&receipt_mint_address(*order_info.key, order.receipt_mint_bump)?)?;
+        close_token_account_signed_with_bump(
+            receipt_mint_info,
+            authority_info,
+            order_info,
+            receipt_token_program,
+            &[ORDER, authority_info.key.as_ref(), &order.id.to_le_bytes()],
+            order.bump,
+        )?;
+    }
+
     // Close the order account.
-    order_info.close(signer_info)?;
+    order_info.close(authority_info)?;
+
+    // Emit the event.
+    OrderClosed {
+        order: *order_info.key,
+        authority: *authority_info.key,
+    }
+    .log();
 
     Ok(())
 }
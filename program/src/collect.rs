@@ -2,76 +2,199 @@ use protobook_api::prelude::*;
 use steel::*;
 
 /// Collects tokens from an expired order.
+/// Orders pulled from the book by advancing the maker nonce are treated as expired.
+/// Anyone may call this to settle the order, but tokens are always paid to the order authority.
+/// The authority may direct tokens to a different owner by signing.
 pub fn process_collect(accounts: &[AccountInfo<'_>], _data: &[u8]) -> ProgramResult {
     // Load accounts.
     let clock = Clock::get()?;
-    let [signer_info, beneficiary_info, _fee_collector_info, mint_info, order_info, vault_info, system_program, token_program, associated_token_program] =
+    let [signer_info, authority_info, beneficiary_a_info, beneficiary_b_info, config_info, fee_info, fee_collector_info, maker_info, market_info, mint_a_info, mint_b_info, order_info, owner_info, unwrap_info, vault_a_info, vault_b_info, system_program, token_program_a, token_program_b, associated_token_program] =
         accounts
     else {
         return Err(ProgramError::NotEnoughAccountKeys);
     };
     signer_info.is_signer()?;
-    mint_info.as_mint()?;
-    let order = order_info
-        .as_account_mut::<Order>(&protobook_api::ID)?
-        .assert_mut(|o| o.authority == *signer_info.key)?
-        .assert_mut(|o| o.expires_at < clock.unix_timestamp)?;
+    let decimals_a = mint_decimals(mint_a_info)?;
+    let decimals_b = mint_decimals(mint_b_info)?;
+    let mut order = OrderV2::load(order_info)?;
+    order
+        .assert_mut_err(
+            |o| o.authority == *authority_info.key,
+            ProtobookError::NotAuthority.into(),
+        )?
+        .assert_mut_err(
+            |o| o.mint_a == *mint_a_info.key,
+            ProtobookError::MintMismatch.into(),
+        )?
+        .assert_mut_err(
+            |o| o.mint_b == *mint_b_info.key,
+            ProtobookError::MintMismatch.into(),
+        )?
+        .assert_mut_err(
+            |o| !o.is_collected(),
+            ProtobookError::AlreadyCollected.into(),
+        )?;
+    let maker_nonce = Maker::nonce_of(maker_info, &order.authority)?;
+    order.assert_mut_err(
+        |o| o.is_expired(clock.unix_timestamp, maker_nonce),
+        ProtobookError::OrderNotExpired.into(),
+    )?;
+    let vault_a = vault_a_info
+        .is_writable()?
+        .as_associated_token_account(order_info.key, &order.mint_a)?;
+    vault_b_info
+        .is_writable()?
+        .as_associated_token_account(order_info.key, &order.mint_b)?;
+    if signer_info.key != authority_info.key && owner_info.key != authority_info.key {
+        return Err(ProtobookError::NotAuthority.into());
+    }
     system_program.is_program(&system_program::ID)?;
-    token_program.is_program(&spl_token::ID)?;
+    token_program_a.is_program(mint_a_info.owner)?;
+    token_program_b.is_program(mint_b_info.owner)?;
     associated_token_program.is_program(&spl_associated_token_account::ID)?;
 
-    // Check if order is filled.
-    let is_filled = order.total_deposits == order.amount_b;
+    // Get transfer amounts.
+    // If deposits are swapped, the authority receives the deposits and any unsold token A.
+    // Otherwise, the deposits are refunded to takers and the authority reclaims all of token A.
+    let (amount_a, amount_b) = order.collectable(vault_a.amount());
 
-    // Validate token accounts and get transfer amount.
-    let amount = if is_filled {
-        mint_info.has_address(&order.mint_b)?;
-        if beneficiary_info.data_is_empty() {
-            create_associated_token_account(
-                signer_info,
+    // Get the protocol fee on the deposits.
+    let fee = Config::fee_of(config_info, fee_collector_info, amount_b)?;
+    let amount_b = amount_b - fee;
+
+    // Record the collection. Orders with a stale nonce expire when collected.
+    let tip = order.tip;
+    order.expires_at = order.expires_at.min(clock.unix_timestamp);
+    order.status = OrderStatus::Collected.into();
+    order.tip = 0;
+    order.save(order_info)?;
+
+    // Take the order off its market's live orders.
+    if order.is_listed() {
+        let market = market_info
+            .is_writable()?
+            .has_address(&order.market)?
+            .as_account_mut::<Market>(&protobook_api::ID)?;
+        market.live_orders -= 1;
+    }
+
+    // Withdraw token A from escrow to the owner.
+    if amount_a > 0 {
+        if is_native_mint(mint_a_info.key) {
+            unwrap_signed(
+                order_info,
+                vault_a_info,
+                mint_a_info,
+                unwrap_info,
+                owner_info,
                 signer_info,
-                beneficiary_info,
-                mint_info,
                 system_program,
-                token_program,
-                associated_token_program,
+                token_program_a,
+                amount_a,
+                &[ORDER, authority_info.key.as_ref(), &order.id.to_le_bytes()],
+                order.bump,
             )?;
         } else {
-            beneficiary_info.as_associated_token_account(&signer_info.key, &order.mint_b)?;
+            if beneficiary_a_info.data_is_empty() {
+                create_associated_token_account(
+                    signer_info,
+                    owner_info,
+                    beneficiary_a_info,
+                    mint_a_info,
+                    system_program,
+                    token_program_a,
+                    associated_token_program,
+                )?;
+            } else {
+                beneficiary_a_info.as_associated_token_account(owner_info.key, &order.mint_a)?;
+            }
+            transfer_checked_signed_with_bump(
+                order_info,
+                vault_a_info,
+                mint_a_info,
+                beneficiary_a_info,
+                token_program_a,
+                amount_a,
+                decimals_a,
+                &[ORDER, authority_info.key.as_ref(), &order.id.to_le_bytes()],
+                order.bump,
+            )?;
         }
-        vault_info.as_associated_token_account(&order_info.key, &order.mint_b)?;
-        order.total_deposits
-    } else {
-        mint_info.has_address(&order.mint_a)?;
-        if beneficiary_info.data_is_empty() {
-            create_associated_token_account(
-                signer_info,
+    }
+
+    // Pay the protocol fee.
+    pay_fee_signed(
+        order_info,
+        vault_b_info,
+        mint_b_info,
+        fee_info,
+        fee_collector_info,
+        signer_info,
+        system_program,
+        token_program_b,
+        associated_token_program,
+        fee,
+        &[ORDER, authority_info.key.as_ref(), &order.id.to_le_bytes()],
+        order.bump,
+    )?;
+
+    // Withdraw token B from escrow to the owner.
+    if amount_b > 0 {
+        if is_native_mint(mint_b_info.key) {
+            unwrap_signed(
+                order_info,
+                vault_b_info,
+                mint_b_info,
+                unwrap_info,
+                owner_info,
                 signer_info,
-                beneficiary_info,
-                mint_info,
                 system_program,
-                token_program,
-                associated_token_program,
+                token_program_b,
+                amount_b,
+                &[ORDER, authority_info.key.as_ref(), &order.id.to_le_bytes()],
+                order.bump,
             )?;
         } else {
-            beneficiary_info.as_associated_token_account(&signer_info.key, &order.mint_a)?;
+            if beneficiary_b_info.data_is_empty() {
+                create_associated_token_account(
+                    signer_info,
+                    owner_info,
+                    beneficiary_b_info,
+                    mint_b_info,
+                    system_program,
+                    token_program_b,
+                    associated_token_program,
+                )?;
+            } else {
+                beneficiary_b_info.as_associated_token_account(owner_info.key, &order.mint_b)?;
+            }
+            transfer_checked_signed_with_bump(
+                order_info,
+                vault_b_info,
+                mint_b_info,
+                beneficiary_b_info,
+                token_program_b,
+                amount_b,
+                decimals_b,
+                &[ORDER, authority_info.key.as_ref(), &order.id.to_le_bytes()],
+                order.bump,
+            )?;
         }
-        vault_info.as_associated_token_account(&order_info.key, &order.mint_a)?;
-        order.amount_a
-    };
+    }
 
-    // Record the collection.
-    order.is_collected = 1;
+    // Pay the tip to the caller.
+    order_info.send(tip, signer_info);
 
-    // Withdraw from escrow to the order authority.
-    transfer_signed(
-        order_info,
-        vault_info,
-        beneficiary_info,
-        token_program,
-        amount,
-        &[ORDER, signer_info.key.as_ref(), &order.id.to_le_bytes()],
-    )?;
+    // Emit the event.
+    OrderCollected {
+        order: *order_info.key,
+        owner: *owner_info.key,
+        amount_a,
+        amount_b,
+        fee,
+        tip,
+    }
+    .log();
 
     Ok(())
 }
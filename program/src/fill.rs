@@ -1,79 +1,304 @@
 use protobook_api::prelude::*;
-use solana_program::log::sol_log;
 use steel::*;
 
 /// Fills an order.
+/// An optional referral fee is paid out of the deposit to the referrer's token account.
 pub fn process_fill(accounts: &[AccountInfo<'_>], data: &[u8]) -> ProgramResult {
     // Parse data.
-    let args = Fill::try_from_bytes(data)?;
+    // Args are followed by an optional merkle proof of the taker's place on the allowlist.
+    let (args, proof) = data
+        .split_at_checked(std::mem::size_of::<Fill>())
+        .ok_or(ProgramError::InvalidInstructionData)?;
+    let args = Fill::try_from_bytes(args)?;
     let amount = u64::from_le_bytes(args.amount);
+    let referral_fee = u64::from_le_bytes(args.referral_fee);
+    let tip = u64::from_le_bytes(args.tip);
+    if referral_fee > MAX_REFERRAL_FEE {
+        return Err(ProtobookError::FeeTooHigh.into());
+    }
+    if proof.len() % 32 != 0 {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    let proof = proof
+        .chunks_exact(32)
+        .map(|node| node.try_into().unwrap())
+        .collect::<Vec<[u8; 32]>>();
 
     // Load accounts.
     let clock = Clock::get()?;
-    let [signer_info, order_info, receipt_info, sender_info, vault_b_info, system_program, token_program] =
+    let [signer_info, beneficiary_info, config_info, fee_info, fee_collector_info, maker_info, mint_a_info, mint_b_info, order_info, receipt_info, receipt_mint_info, receipt_tokens_info, referrer_info, sender_info, unwrap_info, vault_a_info, vault_b_info, system_program, token_program_a, token_program_b, receipt_token_program, associated_token_program] =
         accounts
     else {
         return Err(ProgramError::NotEnoughAccountKeys);
     };
     signer_info.is_signer()?;
-    let order = order_info
-        .as_account_mut::<Order>(&protobook_api::ID)?
-        .assert_mut(|o| o.expires_at > clock.unix_timestamp)?
-        .assert_mut(|o| o.amount_b > o.total_deposits)?;
-    receipt_info.is_writable()?.has_seeds(
-        &[RECEIPT, signer_info.key.as_ref(), order_info.key.as_ref()],
-        &protobook_api::ID,
+    let mut order = OrderV2::load(order_info)?;
+    order
+        .assert_mut_err(
+            |o| o.is_taker(signer_info.key),
+            ProtobookError::TakerNotAllowed.into(),
+        )?
+        .assert_mut_err(
+            |o| o.is_allowlisted(signer_info.key, &proof),
+            ProtobookError::TakerNotAllowed.into(),
+        )?
+        .assert_mut_err(
+            |o| o.starts_at <= clock.unix_timestamp,
+            ProtobookError::OrderNotStarted.into(),
+        )?
+        .assert_mut_err(
+            |o| o.expires_at > clock.unix_timestamp,
+            ProtobookError::OrderExpired.into(),
+        )?
+        .assert_mut_err(
+            |o| o.amount_b > o.total_deposits,
+            ProtobookError::OrderFilled.into(),
+        )?;
+    let maker_nonce = Maker::nonce_of(maker_info, &order.authority)?;
+    order.assert_mut_err(
+        |o| o.nonce == maker_nonce,
+        ProtobookError::OrderCancelled.into(),
     )?;
-    vault_b_info
-        .is_writable()?
-        .as_associated_token_account(&order_info.key, &order.mint_b)?;
-    sender_info
+    mint_b_info.has_address(&order.mint_b)?;
+    let decimals_b = mint_decimals(mint_b_info)?;
+    let vault_b = vault_b_info
         .is_writable()?
-        .as_associated_token_account(&signer_info.key, &order.mint_b)?;
+        .as_associated_token_account(order_info.key, &order.mint_b)?;
     system_program.is_program(&system_program::ID)?;
-    token_program.is_program(&spl_token::ID)?;
+    token_program_b.is_program(mint_b_info.owner)?;
+    receipt_token_program.is_program(&spl_token_2022::ID)?;
+    associated_token_program.is_program(&spl_associated_token_account::ID)?;
 
-    // Create receipt account, if necessary.
-    let receipt = if receipt_info.data_is_empty() {
-        create_program_account::<Receipt>(
-            receipt_info,
-            system_program,
-            signer_info,
-            &protobook_api::ID,
-            &[RECEIPT, signer_info.key.as_ref(), order_info.key.as_ref()],
-        )?;
-        let receipt = receipt_info.as_account_mut::<Receipt>(&protobook_api::ID)?;
-        receipt.authority = *signer_info.key;
-        receipt.deposit = 0;
-        receipt.order = *order_info.key;
-        order.total_receipts += 1;
-        receipt
-    } else {
-        receipt_info.as_account_mut::<Receipt>(&protobook_api::ID)?
-    };
+    // Check the program is not paused.
+    Config::assert_unpaused(config_info)?;
+
+    // Split the referral fee from the deposit.
+    // If the deposit is capped by the remaining size, the fee is scaled down with it.
+    let remaining = order.amount_b - order.total_deposits;
+    let max_amount = pre_fee_amount(mint_b_info, clock.epoch, remaining)?;
+    let (amount, referral) = split_referral(amount, referral_fee, max_amount);
 
-    let sender = sender_info.as_associated_token_account(&signer_info.key, &order.mint_b)?;
-    sol_log(&format!("sender: {:?}", sender.amount()).to_string());
+    // Pay the referral fee to the referrer.
+    if referral > 0 {
+        referrer_info
+            .is_writable()?
+            .as_token_account()?
+            .assert_err(
+                |t| t.mint() == order.mint_b,
+                ProtobookError::InvalidReferrer.into(),
+            )?;
+        if referrer_info.key == vault_b_info.key {
+            return Err(ProtobookError::InvalidReferrer.into());
+        }
+        if is_native_mint(mint_b_info.key) {
+            wrap(signer_info, referrer_info, referral)?;
+        } else {
+            transfer_checked(
+                signer_info,
+                sender_info,
+                mint_b_info,
+                referrer_info,
+                token_program_b,
+                referral,
+                decimals_b,
+            )?;
+        }
+    }
 
     // Lock token B in escrow.
-    let remaining = order.amount_b - order.total_deposits;
-    let amount = amount.min(remaining);
-    transfer(
-        signer_info,
-        sender_info,
-        vault_b_info,
-        token_program,
-        amount,
-    )?;
+    // The deposit is the amount received, net of any transfer fee.
+    // Native SOL is wrapped straight from the signer's lamports.
+    if is_native_mint(mint_b_info.key) {
+        wrap(signer_info, vault_b_info, amount)?;
+    } else {
+        sender_info
+            .is_writable()?
+            .as_associated_token_account(signer_info.key, &order.mint_b)?;
+        transfer_checked(
+            signer_info,
+            sender_info,
+            mint_b_info,
+            vault_b_info,
+            token_program_b,
+            amount,
+            decimals_b,
+        )?;
+    }
+    let amount = vault_b_info.as_token_account()?.amount() - vault_b.amount();
+    if !order.is_valid_fill(amount) {
+        return Err(ProtobookError::InvalidFill.into());
+    }
 
     // Record the deposit.
-    receipt.deposit += amount;
     order.total_deposits += amount;
 
+    // Settle the deposit.
+    let (mut swapped, mut fee) = (0, 0);
+    if order.is_atomic() {
+        // Swap token A to the taker immediately.
+        // No receipt is issued, so there is nothing to tip.
+        if tip > 0 {
+            return Err(ProtobookError::TipNotAllowed.into());
+        }
+        mint_a_info.has_address(&order.mint_a)?;
+        let decimals_a = mint_decimals(mint_a_info)?;
+        token_program_a.is_program(mint_a_info.owner)?;
+        vault_a_info
+            .is_writable()?
+            .as_associated_token_account(order_info.key, &order.mint_a)?;
+        let amount_a = order.amount_a_for(amount);
+        if amount_a == 0 {
+            return Err(ProtobookError::InvalidFill.into());
+        }
+
+        // Charge the same protocol fee as receipts pay on redemption.
+        fee = Config::fee_of(config_info, fee_collector_info, amount_a)?;
+        let amount_a = amount_a - fee;
+        swapped = amount_a;
+        pay_fee_signed(
+            order_info,
+            vault_a_info,
+            mint_a_info,
+            fee_info,
+            fee_collector_info,
+            signer_info,
+            system_program,
+            token_program_a,
+            associated_token_program,
+            fee,
+            &[ORDER, order.authority.as_ref(), &order.id.to_le_bytes()],
+            order.bump,
+        )?;
+        if is_native_mint(mint_a_info.key) {
+            unwrap_signed(
+                order_info,
+                vault_a_info,
+                mint_a_info,
+                unwrap_info,
+                signer_info,
+                signer_info,
+                system_program,
+                token_program_a,
+                amount_a,
+                &[ORDER, order.authority.as_ref(), &order.id.to_le_bytes()],
+                order.bump,
+            )?;
+        } else {
+            if beneficiary_info.data_is_empty() {
+                create_associated_token_account(
+                    signer_info,
+                    signer_info,
+                    beneficiary_info,
+                    mint_a_info,
+                    system_program,
+                    token_program_a,
+                    associated_token_program,
+                )?;
+            } else {
+                beneficiary_info.as_associated_token_account(signer_info.key, &order.mint_a)?;
+            }
+            transfer_checked_signed_with_bump(
+                order_info,
+                vault_a_info,
+                mint_a_info,
+                beneficiary_info,
+                token_program_a,
+                amount_a,
+                decimals_a,
+                &[ORDER, order.authority.as_ref(), &order.id.to_le_bytes()],
+                order.bump,
+            )?;
+        }
+    } else if order.is_tokenized() {
+        // Mint receipt tokens to the taker 1:1 with the deposit.
+        // No receipt account is issued, so there is nothing to tip.
+        if tip > 0 {
+            return Err(ProtobookError::TipNotAllowed.into());
+        }
+        receipt_mint_info
+            .is_writable()?
+            .has_address(&receipt_mint_address(*order_info.key, order.receipt_mint_bump)?)?;
+        if receipt_tokens_info.data_is_empty() {
+            create_associated_token_account(
+                signer_info,
+                signer_info,
+                receipt_tokens_info,
+                receipt_mint_info,
+                system_program,
+                receipt_token_program,
+                associated_token_program,
+            )?;
+        } else {
+            receipt_tokens_info
+                .is_writable()?
+                .as_associated_token_account(signer_info.key, receipt_mint_info.key)?;
+        }
+        mint_to_signed_with_bump(
+            receipt_mint_info,
+            receipt_tokens_info,
+            order_info,
+            receipt_token_program,
+            amount,
+            &[ORDER, order.authority.as_ref(), &order.id.to_le_bytes()],
+            order.bump,
+        )?;
+        order.total_receipts += amount;
+    } else {
+        // Record the deposit on a receipt, creating it if necessary.
+        // An existing receipt is checked against its stored bump.
+        receipt_info.is_writable()?;
+        if receipt_info.data_is_empty() {
+            let (receipt_address, receipt_bump) = receipt_pda(*signer_info.key, *order_info.key);
+            receipt_info.has_address(&receipt_address)?;
+            create_program_account_with_bump::<ReceiptV2>(
+                receipt_info,
+                system_program,
+                signer_info,
+                &protobook_api::ID,
+                &[RECEIPT, signer_info.key.as_ref(), order_info.key.as_ref()],
+                receipt_bump,
+            )?;
+            let receipt = receipt_info.as_account_mut::<ReceiptV2>(&protobook_api::ID)?;
+            receipt.authority = *signer_info.key;
+            receipt.deposit = 0;
+            receipt.order = *order_info.key;
+            receipt.tip = 0;
+            receipt.bump = receipt_bump;
+            order.total_receipts += 1;
+        }
+        let mut receipt = ReceiptV2::load(receipt_info)?;
+        receipt_info.has_address(&receipt_address(
+            *signer_info.key,
+            *order_info.key,
+            receipt.bump,
+        )?)?;
+        receipt.deposit += amount;
+
+        // Escrow the tip for whoever redeems the receipt.
+        if tip > 0 {
+            receipt.tip += tip;
+            receipt_info.collect(tip, signer_info)?;
+        }
+        receipt.save(receipt_info)?;
+    }
+
     // If filled, expire the order immediately.
     if order.total_deposits == order.amount_b {
         order.expires_at = clock.unix_timestamp;
     }
+    order.save(order_info)?;
+
+    // Emit the event.
+    OrderFilled {
+        order: *order_info.key,
+        taker: *signer_info.key,
+        amount,
+        amount_a: swapped,
+        fee,
+        referral,
+        total_deposits: order.total_deposits,
+    }
+    .log();
 
     Ok(())
 }
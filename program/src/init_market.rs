@@ -0,0 +1,73 @@
+use protobook_api::prelude::*;
+use steel::*;
+
+/// Initializes the market for orders offering token A for token B. Only the config admin may call this.
+pub fn process_init_market(accounts: &[AccountInfo<'_>], data: &[u8]) -> ProgramResult {
+    // Parse args.
+    let args = InitMarket::try_from_bytes(data)?;
+    let lot_size = u64::from_le_bytes(args.lot_size);
+    let min_order_size = u64::from_le_bytes(args.min_order_size);
+    let tick_size = u64::from_le_bytes(args.tick_size);
+    if lot_size == 0 || tick_size == 0 || min_order_size < lot_size {
+        return Err(ProtobookError::InvalidTerms.into());
+    }
+
+    // Load accounts.
+    let [signer_info, config_info, market_info, mint_a_info, mint_b_info, system_program] =
+        accounts
+    else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    signer_info.is_signer()?;
+    config_info
+        .as_account::<Config>(&protobook_api::ID)?
+        .assert_err(
+            |c| c.admin == *signer_info.key,
+            ProtobookError::NotAuthority.into(),
+        )?;
+    mint_decimals(mint_a_info)?;
+    mint_decimals(mint_b_info)?;
+    let (market_address, bump) = market_pda(*mint_a_info.key, *mint_b_info.key);
+    market_info
+        .is_empty()?
+        .is_writable()?
+        .has_address(&market_address)?;
+    system_program.is_program(&system_program::ID)?;
+
+    // Safety check.
+    if mint_a_info.key == mint_b_info.key {
+        return Err(ProtobookError::SameMint.into());
+    }
+
+    // Create the market account.
+    create_program_account_with_bump::<Market>(
+        market_info,
+        system_program,
+        signer_info,
+        &protobook_api::ID,
+        &[MARKET, mint_a_info.key.as_ref(), mint_b_info.key.as_ref()],
+        bump,
+    )?;
+    let market = market_info.as_account_mut::<Market>(&protobook_api::ID)?;
+    market.mint_a = *mint_a_info.key;
+    market.mint_b = *mint_b_info.key;
+    market.lot_size = lot_size;
+    market.min_order_size = min_order_size;
+    market.tick_size = tick_size;
+    market.live_orders = 0;
+    market.total_orders = 0;
+    market.bump = bump;
+
+    // Emit the event.
+    MarketInitialized {
+        market: *market_info.key,
+        mint_a: market.mint_a,
+        mint_b: market.mint_b,
+        lot_size: market.lot_size,
+        min_order_size: market.min_order_size,
+        tick_size: market.tick_size,
+    }
+    .log();
+
+    Ok(())
+}
@@ -0,0 +1,76 @@
+use protobook_api::prelude::*;
+use solana_program::{
+    bpf_loader_upgradeable::{self, UpgradeableLoaderState},
+    program_utils::limited_deserialize,
+};
+use steel::*;
+
+/// Initializes the config. Only the upgrade authority of the program may call this.
+pub fn process_initialize_config(accounts: &[AccountInfo<'_>], data: &[u8]) -> ProgramResult {
+    // Parse args.
+    let args = InitializeConfig::try_from_bytes(data)?;
+    let fee = u64::from_le_bytes(args.fee);
+    if fee > MAX_FEE {
+        return Err(ProtobookError::FeeTooHigh.into());
+    }
+
+    // Load accounts.
+    let [signer_info, config_info, program_data_info, system_program] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    signer_info.is_signer()?;
+    let (config_address, config_bump) = config_pda();
+    config_info
+        .is_empty()?
+        .is_writable()?
+        .has_address(&config_address)?;
+    program_data_info
+        .has_address(&bpf_loader_upgradeable::get_program_data_address(
+            &protobook_api::ID,
+        ))?
+        .has_owner(&bpf_loader_upgradeable::ID)?;
+    system_program.is_program(&system_program::ID)?;
+
+    // Check the signer is the upgrade authority.
+    let program_data = program_data_info.try_borrow_data()?;
+    let metadata = program_data
+        .get(..UpgradeableLoaderState::size_of_programdata_metadata())
+        .ok_or(ProgramError::InvalidAccountData)?;
+    let Ok(UpgradeableLoaderState::ProgramData {
+        upgrade_authority_address,
+        ..
+    }) = limited_deserialize(metadata, metadata.len() as u64)
+    else {
+        return Err(ProgramError::InvalidAccountData);
+    };
+    if upgrade_authority_address != Some(*signer_info.key) {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // Create the config account.
+    create_program_account_with_bump::<Config>(
+        config_info,
+        system_program,
+        signer_info,
+        &protobook_api::ID,
+        &[CONFIG],
+        config_bump,
+    )?;
+    let config = config_info.as_account_mut::<Config>(&protobook_api::ID)?;
+    config.admin = *signer_info.key;
+    config.fee = fee;
+    config.fee_collector = args.fee_collector;
+    config.is_paused = 0;
+    config.bump = config_bump;
+
+    // Emit the event.
+    ConfigUpdated {
+        admin: config.admin,
+        fee: config.fee,
+        fee_collector: config.fee_collector,
+        is_paused: config.is_paused,
+    }
+    .log();
+
+    Ok(())
+}
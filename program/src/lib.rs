@@ -1,16 +1,32 @@
+mod amend;
 mod cancel;
-mod collect;
+mod cancel_all;
 mod close;
+mod collect;
 mod fill;
+mod init_market;
+mod initialize_config;
+mod migrate;
 mod open;
 mod redeem;
+mod replace;
+mod transfer_receipt;
+mod update_config;
 
+use amend::*;
 use cancel::*;
-use collect::*;
+use cancel_all::*;
 use close::*;
+use collect::*;
 use fill::*;
+use init_market::*;
+use initialize_config::*;
+use migrate::*;
 use open::*;
 use redeem::*;
+use replace::*;
+use transfer_receipt::*;
+use update_config::*;
 
 use protobook_api::prelude::*;
 use steel::*;
@@ -23,12 +39,20 @@ pub fn process_instruction(
     let (ix, data) = parse_instruction(&protobook_api::ID, program_id, data)?;
 
     match ix {
+        ProtobookInstruction::Amend => process_amend(accounts, data)?,
         ProtobookInstruction::Cancel => process_cancel(accounts, data)?,
+        ProtobookInstruction::CancelAll => process_cancel_all(accounts, data)?,
         ProtobookInstruction::Close => process_close(accounts, data)?,
         ProtobookInstruction::Collect => process_collect(accounts, data)?,
         ProtobookInstruction::Fill => process_fill(accounts, data)?,
+        ProtobookInstruction::InitMarket => process_init_market(accounts, data)?,
+        ProtobookInstruction::InitializeConfig => process_initialize_config(accounts, data)?,
+        ProtobookInstruction::Migrate => process_migrate(accounts, data)?,
         ProtobookInstruction::Open => process_open(accounts, data)?,
         ProtobookInstruction::Redeem => process_redeem(accounts, data)?,
+        ProtobookInstruction::Replace => process_replace(accounts, data)?,
+        ProtobookInstruction::TransferReceipt => process_transfer_receipt(accounts, data)?,
+        ProtobookInstruction::UpdateConfig => process_update_config(accounts, data)?,
     }
 
     Ok(())
@@ -0,0 +1,84 @@
+use protobook_api::prelude::*;
+use solana_program::rent::Rent;
+use steel::*;
+
+/// Migrates an order or receipt account from its original layout to the current one.
+/// Anyone may call this, paying for any additional rent of the larger account.
+pub fn process_migrate(accounts: &[AccountInfo<'_>], _data: &[u8]) -> ProgramResult {
+    // Load accounts.
+    let [signer_info, account_info, system_program] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    signer_info.is_signer()?;
+    account_info.is_writable()?.has_owner(&protobook_api::ID)?;
+    system_program.is_program(&system_program::ID)?;
+
+    let discriminator = account_info.try_borrow_data()?.first().copied();
+    match discriminator.map(ProtobookAccount::try_from) {
+        Some(Ok(ProtobookAccount::OrderV1)) => migrate_order(signer_info, account_info),
+        Some(Ok(ProtobookAccount::ReceiptV1)) => migrate_receipt(signer_info, account_info),
+        _ => Err(ProgramError::InvalidAccountData),
+    }
+}
+
+/// Upgrades an order to the current layout, storing its bump.
+fn migrate_order<'info>(
+    signer_info: &AccountInfo<'info>,
+    order_info: &AccountInfo<'info>,
+) -> ProgramResult {
+    let order = *order_info.as_account::<OrderV1>(&protobook_api::ID)?;
+    let (order_address, bump) = order_pda(order.authority, order.id);
+    order_info.has_address(&order_address)?;
+
+    // Upgrade the order to the current layout.
+    // Its nonce is zero, so it stays live unless the authority has since cancelled all orders.
+    let data = OrderV2::migrate(&order_info.try_borrow_data()?, bump)?;
+    write(signer_info, order_info, &data)?;
+
+    // Emit the event.
+    OrderMigrated {
+        order: order_address,
+        authority: order.authority,
+    }
+    .log();
+
+    Ok(())
+}
+
+/// Upgrades a receipt to the current layout, storing its bump.
+fn migrate_receipt<'info>(
+    signer_info: &AccountInfo<'info>,
+    receipt_info: &AccountInfo<'info>,
+) -> ProgramResult {
+    let receipt = *receipt_info.as_account::<ReceiptV1>(&protobook_api::ID)?;
+    let (receipt_address, bump) = receipt_pda(receipt.authority, receipt.order);
+    receipt_info.has_address(&receipt_address)?;
+
+    // Upgrade the receipt to the current layout.
+    let data = ReceiptV2::migrate(&receipt_info.try_borrow_data()?, bump)?;
+    write(signer_info, receipt_info, &data)?;
+
+    // Emit the event.
+    ReceiptMigrated {
+        receipt: receipt_address,
+        authority: receipt.authority,
+    }
+    .log();
+
+    Ok(())
+}
+
+/// Resizes an account to fit the given data, topping up rent, and writes the data.
+fn write<'info>(
+    signer_info: &AccountInfo<'info>,
+    account_info: &AccountInfo<'info>,
+    data: &[u8],
+) -> ProgramResult {
+    let required = Rent::get()?.minimum_balance(data.len());
+    if account_info.lamports() < required {
+        account_info.collect(required - account_info.lamports(), signer_info)?;
+    }
+    account_info.realloc(data.len(), false)?;
+    account_info.try_borrow_mut_data()?.copy_from_slice(data);
+    Ok(())
+}
@@ -1,8 +1,13 @@
 use protobook_api::prelude::*;
-use spl_associated_token_account::get_associated_token_address;
+use spl_associated_token_account::get_associated_token_address_with_program_id;
+use spl_token_2022::extension::ExtensionType;
 use steel::*;
 
 /// Opens an order.
+/// If the id is AUTO_ID, the next id is assigned from the given counter lane of the authority.
+/// Otherwise the id must be below AUTO_ID_START, so it never collides with an assigned id.
+/// The id of the order is returned through return data.
+/// If listed, the order must be on the price grid of the market for its mints.
 pub fn process_open(accounts: &[AccountInfo<'_>], data: &[u8]) -> ProgramResult {
     // Parse args.
     let clock = Clock::get()?;
@@ -11,54 +16,105 @@ pub fn process_open(accounts: &[AccountInfo<'_>], data: &[u8]) -> ProgramResult
     let amount_b = u64::from_le_bytes(args.amount_b);
     let expires_at = i64::from_le_bytes(args.expires_at);
     let id = u64::from_le_bytes(args.id);
-    if amount_a == 0 || amount_b == 0 || expires_at < clock.unix_timestamp {
-        return Err(ProgramError::InvalidArgument);
+    let is_atomic = u64::from_le_bytes(args.is_atomic);
+    let is_listed = u64::from_le_bytes(args.is_listed);
+    let is_pro_rata = u64::from_le_bytes(args.is_pro_rata);
+    let is_tokenized = u64::from_le_bytes(args.is_tokenized);
+    let lane = u16::from_le_bytes(args.lane);
+    let lot_size = u64::from_le_bytes(args.lot_size);
+    let min_fill = u64::from_le_bytes(args.min_fill);
+    let starts_at = i64::from_le_bytes(args.starts_at);
+    let tip = u64::from_le_bytes(args.tip);
+    if is_atomic > 1 || is_listed > 1 || is_pro_rata > 1 || is_tokenized > 1 {
+        return Err(ProtobookError::InvalidFlag.into());
+    }
+    if amount_a == 0
+        || amount_b == 0
+        || expires_at < clock.unix_timestamp
+        || is_atomic + is_tokenized > 1
+        || lot_size > amount_b
+        || min_fill > amount_b
+        || starts_at >= expires_at
+        || (id != AUTO_ID && id >= AUTO_ID_START)
+    {
+        return Err(ProtobookError::InvalidTerms.into());
     }
 
     // Load accounts.
-    let [signer_info, _fee_collector_info, mint_a_info, mint_b_info, order_info, sender_info, vault_a_info, vault_b_info, system_program, token_program, associated_token_program] =
+    let [signer_info, config_info, counter_info, maker_info, market_info, mint_a_info, mint_b_info, order_info, receipt_mint_info, sender_info, vault_a_info, vault_b_info, system_program, token_program_a, token_program_b, receipt_token_program, associated_token_program] =
         accounts
     else {
         return Err(ProgramError::NotEnoughAccountKeys);
     };
     signer_info.is_signer()?;
-    mint_a_info.as_mint()?;
-    mint_b_info.as_mint()?;
-    order_info.is_empty()?.is_writable()?.has_seeds(
-        &[ORDER, signer_info.key.as_ref(), args.id.as_ref()],
-        &protobook_api::ID,
-    )?;
-    sender_info
+    let decimals_a = mint_decimals(mint_a_info)?;
+    let decimals_b = mint_decimals(mint_b_info)?;
+    system_program.is_program(&system_program::ID)?;
+
+    // Assign the next id from the counter lane, creating it if necessary.
+    let id = if id == AUTO_ID {
+        Counter::load_or_create(counter_info, signer_info, system_program, lane)?.assign_id()?
+    } else {
+        id
+    };
+    let (order_address, order_bump) = order_pda(*signer_info.key, id);
+    order_info
+        .is_empty()?
         .is_writable()?
-        .as_associated_token_account(&signer_info.key, &mint_a_info.key)?;
+        .has_address(&order_address)?;
     vault_a_info
         .is_writable()?
-        .has_address(&get_associated_token_address(
-            &order_info.key,
-            &mint_a_info.key,
+        .has_address(&get_associated_token_address_with_program_id(
+            order_info.key,
+            mint_a_info.key,
+            mint_a_info.owner,
         ))?;
     vault_b_info
         .is_writable()?
-        .has_address(&get_associated_token_address(
-            &order_info.key,
-            &mint_b_info.key,
+        .has_address(&get_associated_token_address_with_program_id(
+            order_info.key,
+            mint_b_info.key,
+            mint_b_info.owner,
         ))?;
-    system_program.is_program(&system_program::ID)?;
-    token_program.is_program(&spl_token::ID)?;
+    token_program_a.is_program(mint_a_info.owner)?;
+    token_program_b.is_program(mint_b_info.owner)?;
+    receipt_token_program.is_program(&spl_token_2022::ID)?;
     associated_token_program.is_program(&spl_associated_token_account::ID)?;
 
+    // Check the program is not paused.
+    Config::assert_unpaused(config_info)?;
+
     // Safety check.
-    assert!(mint_a_info.key != mint_b_info.key);
+    if mint_a_info.key == mint_b_info.key {
+        return Err(ProtobookError::SameMint.into());
+    }
+
+    // Create maker account, if necessary.
+    let maker = Maker::load_or_create(maker_info, signer_info, system_program)?;
 
     // Create an order.
-    create_program_account::<Order>(
+    create_program_account_with_bump::<OrderV2>(
         order_info,
         system_program,
         signer_info,
         &protobook_api::ID,
-        &[ORDER, signer_info.key.as_ref(), args.id.as_ref()],
+        &[ORDER, signer_info.key.as_ref(), &id.to_le_bytes()],
+        order_bump,
     )?;
-    let order = order_info.as_account_mut::<Order>(&protobook_api::ID)?;
+    let order = order_info.as_account_mut::<OrderV2>(&protobook_api::ID)?;
+    order.version = ORDER_VERSION;
+    order.status = OrderStatus::Open.into();
+    order.bump = order_bump;
+    order.flags = 0;
+    if is_atomic == 1 {
+        order.flags |= ORDER_FLAG_ATOMIC;
+    }
+    if is_pro_rata == 1 {
+        order.flags |= ORDER_FLAG_PRO_RATA;
+    }
+    if is_tokenized == 1 {
+        order.flags |= ORDER_FLAG_TOKENIZED;
+    }
     order.authority = *signer_info.key;
     order.amount_a = amount_a;
     order.amount_b = amount_b;
@@ -69,7 +125,60 @@ pub fn process_open(accounts: &[AccountInfo<'_>], data: &[u8]) -> ProgramResult
     order.total_deposits = 0;
     order.total_receipts = 0;
     order.total_redeemed = 0;
-    order.is_collected = 0;
+    order.taker = args.taker;
+    order.merkle_root = args.merkle_root;
+    order.min_fill = min_fill;
+    order.lot_size = lot_size;
+    order.starts_at = starts_at;
+    order.nonce = maker.nonce;
+    order.tip = tip;
+    order.market = Pubkey::default();
+
+    // Escrow the tip for whoever collects the order.
+    if tip > 0 {
+        order_info.collect(tip, signer_info)?;
+    }
+
+    // Create a Token-2022 receipt mint, owned by the order, with the same decimals as token B.
+    // The order is also the mint's close authority, so the rent can be reclaimed on close.
+    if is_tokenized == 1 {
+        let (receipt_mint_address, receipt_mint_bump) = receipt_mint_pda(*order_info.key);
+        receipt_mint_info
+            .is_empty()?
+            .is_writable()?
+            .has_address(&receipt_mint_address)?;
+        let space = ExtensionType::try_calculate_account_len::<spl_token_2022::state::Mint>(&[
+            ExtensionType::MintCloseAuthority,
+        ])?;
+        allocate_account_with_bump(
+            receipt_mint_info,
+            system_program,
+            signer_info,
+            space,
+            &spl_token_2022::ID,
+            &[RECEIPT_MINT, order_info.key.as_ref()],
+            receipt_mint_bump,
+        )?;
+        solana_program::program::invoke(
+            &spl_token_2022::instruction::initialize_mint_close_authority(
+                &spl_token_2022::ID,
+                receipt_mint_info.key,
+                Some(order_info.key),
+            )?,
+            &[receipt_mint_info.clone(), receipt_token_program.clone()],
+        )?;
+        solana_program::program::invoke(
+            &spl_token_2022::instruction::initialize_mint2(
+                &spl_token_2022::ID,
+                receipt_mint_info.key,
+                order_info.key,
+                None,
+                decimals_b,
+            )?,
+            &[receipt_mint_info.clone(), receipt_token_program.clone()],
+        )?;
+        order.receipt_mint_bump = receipt_mint_bump;
+    }
 
     // Create escrow vaults for tokens A and B.
     if vault_a_info.data_is_empty() {
@@ -79,7 +188,7 @@ pub fn process_open(accounts: &[AccountInfo<'_>], data: &[u8]) -> ProgramResult
             vault_a_info,
             mint_a_info,
             system_program,
-            token_program,
+            token_program_a,
             associated_token_program,
         )?;
     }
@@ -90,19 +199,71 @@ pub fn process_open(accounts: &[AccountInfo<'_>], data: &[u8]) -> ProgramResult
             vault_b_info,
             mint_b_info,
             system_program,
-            token_program,
+            token_program_b,
             associated_token_program,
         )?;
     }
 
     // Lock token A in escrow.
-    transfer(
-        signer_info,
-        sender_info,
-        vault_a_info,
-        token_program,
-        amount_a,
-    )?;
+    // The order offers the amount received, net of any transfer fee.
+    // Native SOL is wrapped straight from the signer's lamports.
+    let balance = vault_a_info.as_token_account()?.amount();
+    if is_native_mint(mint_a_info.key) {
+        wrap(signer_info, vault_a_info, amount_a)?;
+    } else {
+        sender_info
+            .is_writable()?
+            .as_associated_token_account(signer_info.key, mint_a_info.key)?;
+        transfer_checked(
+            signer_info,
+            sender_info,
+            mint_a_info,
+            vault_a_info,
+            token_program_a,
+            amount_a,
+            decimals_a,
+        )?;
+    }
+    let received = vault_a_info.as_token_account()?.amount() - balance;
+    if received == 0 {
+        return Err(ProtobookError::InvalidTerms.into());
+    }
+    order.amount_a = received;
+
+    // List the order on the market for its mints.
+    // The price grid is checked against the amount received, net of any transfer fee.
+    if is_listed == 1 {
+        let market = market_info
+            .is_writable()?
+            .as_account_mut::<Market>(&protobook_api::ID)?
+            .assert_mut_err(
+                |m| m.mint_a == order.mint_a && m.mint_b == order.mint_b,
+                ProtobookError::MintMismatch.into(),
+            )?
+            .assert_mut_err(
+                |m| m.is_valid_order(order.amount_a, order.amount_b),
+                ProtobookError::OffMarketGrid.into(),
+            )?;
+        market.live_orders += 1;
+        market.total_orders += 1;
+        order.market = *market_info.key;
+    }
+
+    // Emit the event.
+    OrderOpened {
+        authority: *signer_info.key,
+        order: *order_info.key,
+        mint_a: order.mint_a,
+        mint_b: order.mint_b,
+        amount_a: order.amount_a,
+        amount_b: order.amount_b,
+        expires_at: order.expires_at,
+        id: order.id,
+    }
+    .log();
+
+    // Return the id of the order.
+    solana_program::program::set_return_data(&id.to_le_bytes());
 
     Ok(())
 }
@@ -2,53 +2,121 @@ use protobook_api::prelude::*;
 use steel::*;
 
 /// Redeems a receipt for an expired order.
+/// Receipts of orders pulled from the book by advancing the maker nonce are treated as expired.
+/// Anyone may call this to settle the receipt, but tokens are always paid to the receipt authority.
+/// The authority may direct tokens to a different owner by signing.
+/// Receipt tokens of tokenized orders are burned instead, so their holder must sign.
 pub fn process_redeem(accounts: &[AccountInfo<'_>], _data: &[u8]) -> ProgramResult {
     // Load accounts.
     let clock = Clock::get()?;
-    let [signer_info, beneficiary_info, mint_info, order_info, receipt_info, vault_info, system_program, token_program, associated_token_program] =
+    let [signer_info, authority_info, beneficiary_info, config_info, fee_info, fee_collector_info, maker_info, mint_info, order_info, owner_info, receipt_info, receipt_mint_info, receipt_tokens_info, unwrap_info, vault_info, system_program, token_program, receipt_token_program, associated_token_program] =
         accounts
     else {
         return Err(ProgramError::NotEnoughAccountKeys);
     };
     signer_info.is_signer()?;
-    mint_info.as_mint()?;
-    let order = order_info
-        .as_account_mut::<Order>(&protobook_api::ID)?
-        .assert_mut(|o| o.expires_at < clock.unix_timestamp)?;
-    let receipt = receipt_info
-        .as_account_mut::<Receipt>(&protobook_api::ID)?
-        .assert_mut(|r| r.authority == *signer_info.key)?;
+    let decimals = mint_decimals(mint_info)?;
+    let mut order = OrderV2::load(order_info)?;
+    let maker_nonce = Maker::nonce_of(maker_info, &order.authority)?;
+    order.assert_mut_err(
+        |o| o.is_expired(clock.unix_timestamp, maker_nonce),
+        ProtobookError::OrderNotExpired.into(),
+    )?;
+    if signer_info.key != authority_info.key && owner_info.key != authority_info.key {
+        return Err(ProtobookError::NotAuthority.into());
+    }
     system_program.is_program(&system_program::ID)?;
-    token_program.is_program(&spl_token::ID)?;
+    token_program.is_program(mint_info.owner)?;
+    receipt_token_program.is_program(&spl_token_2022::ID)?;
     associated_token_program.is_program(&spl_associated_token_account::ID)?;
 
-    // Check if order is filled.
-    let is_filled = order.total_deposits == order.amount_b;
+    // Get the deposit to redeem.
+    let (deposit, tip) = if order.is_tokenized() {
+        if signer_info.key != authority_info.key {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        receipt_mint_info
+            .is_writable()?
+            .has_address(&receipt_mint_address(*order_info.key, order.receipt_mint_bump)?)?;
+        let receipt_tokens = receipt_tokens_info
+            .is_writable()?
+            .as_associated_token_account(authority_info.key, receipt_mint_info.key)?;
+        (receipt_tokens.amount(), 0)
+    } else {
+        let mut receipt = ReceiptV2::load(receipt_info)?;
+        receipt
+            .assert_mut_err(
+                |r| r.authority == *authority_info.key,
+                ProtobookError::NotAuthority.into(),
+            )?
+            .assert_mut_err(
+                |r| r.order == *order_info.key,
+                ProtobookError::ReceiptOrderMismatch.into(),
+            )?;
+        (receipt.deposit, receipt.tip)
+    };
+    if deposit == 0 {
+        return Err(ProtobookError::NothingToRedeem.into());
+    }
 
     // Validate token accounts and get transfer amount.
-    let amount = if is_filled {
+    // Proceeds of a swap are charged the protocol fee, while refunds are not.
+    let (amount, fee) = if order.is_swapped() {
         mint_info.has_address(&order.mint_a)?;
-        if beneficiary_info.data_is_empty() {
-            create_associated_token_account(
-                signer_info,
-                signer_info,
-                beneficiary_info,
-                mint_info,
-                system_program,
-                token_program,
-                associated_token_program,
-            )?;
-        } else {
-            beneficiary_info.as_associated_token_account(&signer_info.key, &order.mint_a)?;
-        }
-        vault_info.as_associated_token_account(&order_info.key, &order.mint_a)?;
-        ((order.amount_a as u128 * receipt.deposit as u128) / order.total_deposits as u128) as u64
+        vault_info.as_associated_token_account(order_info.key, &order.mint_a)?;
+        let amount = order.amount_a_for(deposit);
+        let fee = Config::fee_of(config_info, fee_collector_info, amount)?;
+        (amount - fee, fee)
     } else {
         mint_info.has_address(&order.mint_b)?;
+        vault_info.as_associated_token_account(order_info.key, &order.mint_b)?;
+        (deposit, 0)
+    };
+
+    // Record the redemption.
+    if order.is_tokenized() {
+        order.total_redeemed += deposit;
+    } else {
+        order.total_redeemed += 1;
+    }
+    order.save(order_info)?;
+
+    // Pay the protocol fee.
+    pay_fee_signed(
+        order_info,
+        vault_info,
+        mint_info,
+        fee_info,
+        fee_collector_info,
+        signer_info,
+        system_program,
+        token_program,
+        associated_token_program,
+        fee,
+        &[ORDER, order.authority.as_ref(), &order.id.to_le_bytes()],
+        order.bump,
+    )?;
+
+    // Withdraw from escrow to the owner.
+    if is_native_mint(mint_info.key) {
+        unwrap_signed(
+            order_info,
+            vault_info,
+            mint_info,
+            unwrap_info,
+            owner_info,
+            signer_info,
+            system_program,
+            token_program,
+            amount,
+            &[ORDER, order.authority.as_ref(), &order.id.to_le_bytes()],
+            order.bump,
+        )?;
+    } else {
         if beneficiary_info.data_is_empty() {
             create_associated_token_account(
                 signer_info,
-                signer_info,
+                owner_info,
                 beneficiary_info,
                 mint_info,
                 system_program,
@@ -56,27 +124,50 @@ pub fn process_redeem(accounts: &[AccountInfo<'_>], _data: &[u8]) -> ProgramResu
                 associated_token_program,
             )?;
         } else {
-            beneficiary_info.as_associated_token_account(&signer_info.key, &order.mint_b)?;
+            beneficiary_info.as_associated_token_account(owner_info.key, mint_info.key)?;
         }
-        vault_info.as_associated_token_account(&order_info.key, &order.mint_b)?;
-        receipt.deposit
-    };
+        transfer_checked_signed_with_bump(
+            order_info,
+            vault_info,
+            mint_info,
+            beneficiary_info,
+            token_program,
+            amount,
+            decimals,
+            &[ORDER, order.authority.as_ref(), &order.id.to_le_bytes()],
+            order.bump,
+        )?;
+    }
 
-    // Record the redemption.
-    order.total_redeemed += 1;
+    if order.is_tokenized() {
+        // Burn the receipt tokens.
+        burn(
+            receipt_tokens_info,
+            receipt_mint_info,
+            signer_info,
+            receipt_token_program,
+            deposit,
+        )?;
+    } else {
+        // Pay the tip to the caller.
+        receipt_info.send(tip, signer_info);
 
-    // Withdraw from escrow to the receipt authority.
-    transfer_signed(
-        order_info,
-        vault_info,
-        beneficiary_info,
-        token_program,
-        amount,
-        &[ORDER, order.authority.as_ref(), &order.id.to_le_bytes()],
-    )?;
+        // Close the receipt account.
+        receipt_info.close(authority_info)?;
+    }
 
-    // Close the order account.
-    receipt_info.close(&signer_info)?;
+    // Emit the event.
+    ReceiptRedeemed {
+        order: *order_info.key,
+        authority: *authority_info.key,
+        owner: *owner_info.key,
+        mint: *mint_info.key,
+        deposit,
+        amount,
+        fee,
+        tip,
+    }
+    .log();
 
     Ok(())
 }
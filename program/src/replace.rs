@@ -0,0 +1,290 @@
+use protobook_api::prelude::*;
+use spl_associated_token_account::get_associated_token_address_with_program_id;
+use steel::*;
+
+/// Cancels an order and opens a new one in its place.
+/// Tokenized orders cannot be replaced, since the new order would need its own receipt mint.
+/// The new id must be below AUTO_ID_START, so it never collides with an id assigned by a counter.
+pub fn process_replace(accounts: &[AccountInfo<'_>], data: &[u8]) -> ProgramResult {
+    // Parse args.
+    let clock = Clock::get()?;
+    let args = Replace::try_from_bytes(data)?;
+    let amount_a = u64::from_le_bytes(args.amount_a);
+    let amount_b = u64::from_le_bytes(args.amount_b);
+    let expires_at = i64::from_le_bytes(args.expires_at);
+    let id = u64::from_le_bytes(args.id);
+    if amount_a == 0 || amount_b == 0 || expires_at < clock.unix_timestamp || id >= AUTO_ID_START {
+        return Err(ProtobookError::InvalidTerms.into());
+    }
+
+    // Load accounts.
+    let [signer_info, beneficiary_b_info, config_info, fee_info, fee_collector_info, maker_info, market_info, mint_a_info, mint_b_info, new_order_info, new_vault_a_info, new_vault_b_info, order_info, sender_info, vault_a_info, vault_b_info, system_program, token_program_a, token_program_b, associated_token_program] =
+        accounts
+    else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    signer_info.is_signer()?;
+    let decimals_a = mint_decimals(mint_a_info)?;
+    let decimals_b = mint_decimals(mint_b_info)?;
+    let mut order = OrderV2::load(order_info)?;
+    order
+        .assert_mut_err(
+            |o| o.authority == *signer_info.key,
+            ProtobookError::NotAuthority.into(),
+        )?
+        .assert_mut_err(
+            |o| o.mint_a == *mint_a_info.key,
+            ProtobookError::MintMismatch.into(),
+        )?
+        .assert_mut_err(
+            |o| o.mint_b == *mint_b_info.key,
+            ProtobookError::MintMismatch.into(),
+        )?
+        .assert_mut_err(
+            |o| o.expires_at > clock.unix_timestamp,
+            ProtobookError::OrderExpired.into(),
+        )?
+        .assert_mut_err(|o| !o.is_tokenized(), ProtobookError::OrderTokenized.into())?;
+    let vault_a = vault_a_info
+        .is_writable()?
+        .as_associated_token_account(order_info.key, &order.mint_a)?;
+    vault_b_info
+        .is_writable()?
+        .as_associated_token_account(order_info.key, &order.mint_b)?;
+    let (new_order_address, new_order_bump) = order_pda(*signer_info.key, id);
+    new_order_info
+        .is_empty()?
+        .is_writable()?
+        .has_address(&new_order_address)?;
+    new_vault_a_info
+        .is_writable()?
+        .has_address(&get_associated_token_address_with_program_id(
+            new_order_info.key,
+            mint_a_info.key,
+            mint_a_info.owner,
+        ))?;
+    new_vault_b_info
+        .is_writable()?
+        .has_address(&get_associated_token_address_with_program_id(
+            new_order_info.key,
+            mint_b_info.key,
+            mint_b_info.owner,
+        ))?;
+    sender_info
+        .is_writable()?
+        .as_associated_token_account(signer_info.key, mint_a_info.key)?;
+    system_program.is_program(&system_program::ID)?;
+    token_program_a.is_program(mint_a_info.owner)?;
+    token_program_b.is_program(mint_b_info.owner)?;
+    associated_token_program.is_program(&spl_associated_token_account::ID)?;
+
+    // Check the program is not paused.
+    Config::assert_unpaused(config_info)?;
+
+    // Validate the new terms against the settings carried over from the order.
+    if order.lot_size > amount_b || order.min_fill > amount_b || order.starts_at >= expires_at {
+        return Err(ProtobookError::InvalidTerms.into());
+    }
+
+    // The new order stays on the market of a listed order, and must be on its price grid.
+    // The order is collected as the new one is opened, so the market's live orders are unchanged.
+    if order.is_listed() {
+        let market = market_info
+            .is_writable()?
+            .has_address(&order.market)?
+            .as_account_mut::<Market>(&protobook_api::ID)?
+            .assert_mut_err(
+                |m| m.is_valid_order(amount_a, amount_b),
+                ProtobookError::OffMarketGrid.into(),
+            )?;
+        market.total_orders += 1;
+    }
+
+    // Create maker account, if necessary.
+    // Orders pulled from the book by advancing the nonce cannot be replaced.
+    let maker = Maker::load_or_create(maker_info, signer_info, system_program)?;
+    order.assert_mut_err(
+        |o| o.nonce == maker.nonce,
+        ProtobookError::OrderCancelled.into(),
+    )?;
+
+    // Cancel and collect the order.
+    // Receipts on the order are redeemed as usual.
+    let (claim_a, claim_b) = order.collectable(vault_a.amount());
+    let fee = Config::fee_of(config_info, fee_collector_info, claim_b)?;
+    let tip = order.tip;
+    order.expires_at = clock.unix_timestamp;
+    order.status = OrderStatus::Collected.into();
+    order.tip = 0;
+    order.save(order_info)?;
+
+    // Pay the protocol fee on the deposits.
+    pay_fee_signed(
+        order_info,
+        vault_b_info,
+        mint_b_info,
+        fee_info,
+        fee_collector_info,
+        signer_info,
+        system_program,
+        token_program_b,
+        associated_token_program,
+        fee,
+        &[ORDER, signer_info.key.as_ref(), &order.id.to_le_bytes()],
+        order.bump,
+    )?;
+
+    // Withdraw token B from escrow to the order authority.
+    if claim_b > fee {
+        if beneficiary_b_info.data_is_empty() {
+            create_associated_token_account(
+                signer_info,
+                signer_info,
+                beneficiary_b_info,
+                mint_b_info,
+                system_program,
+                token_program_b,
+                associated_token_program,
+            )?;
+        } else {
+            beneficiary_b_info.as_associated_token_account(signer_info.key, &order.mint_b)?;
+        }
+        transfer_checked_signed_with_bump(
+            order_info,
+            vault_b_info,
+            mint_b_info,
+            beneficiary_b_info,
+            token_program_b,
+            claim_b - fee,
+            decimals_b,
+            &[ORDER, signer_info.key.as_ref(), &order.id.to_le_bytes()],
+            order.bump,
+        )?;
+    }
+
+    // Create the new order with the same settings.
+    create_program_account_with_bump::<OrderV2>(
+        new_order_info,
+        system_program,
+        signer_info,
+        &protobook_api::ID,
+        &[ORDER, signer_info.key.as_ref(), args.id.as_ref()],
+        new_order_bump,
+    )?;
+    let new_order = new_order_info.as_account_mut::<OrderV2>(&protobook_api::ID)?;
+    new_order.version = ORDER_VERSION;
+    new_order.status = OrderStatus::Open.into();
+    new_order.bump = new_order_bump;
+    new_order.flags = order.flags;
+    new_order.authority = *signer_info.key;
+    new_order.amount_a = amount_a;
+    new_order.amount_b = amount_b;
+    new_order.expires_at = expires_at;
+    new_order.id = id;
+    new_order.mint_a = order.mint_a;
+    new_order.mint_b = order.mint_b;
+    new_order.total_deposits = 0;
+    new_order.total_receipts = 0;
+    new_order.total_redeemed = 0;
+    new_order.taker = order.taker;
+    new_order.merkle_root = order.merkle_root;
+    new_order.min_fill = order.min_fill;
+    new_order.lot_size = order.lot_size;
+    new_order.starts_at = order.starts_at;
+    new_order.nonce = maker.nonce;
+    new_order.tip = tip;
+    new_order.market = order.market;
+
+    // Carry the tip over to the new order.
+    order_info.send(tip, new_order_info);
+
+    // Create escrow vaults for tokens A and B.
+    if new_vault_a_info.data_is_empty() {
+        create_associated_token_account(
+            signer_info,
+            new_order_info,
+            new_vault_a_info,
+            mint_a_info,
+            system_program,
+            token_program_a,
+            associated_token_program,
+        )?;
+    }
+    if new_vault_b_info.data_is_empty() {
+        create_associated_token_account(
+            signer_info,
+            new_order_info,
+            new_vault_b_info,
+            mint_b_info,
+            system_program,
+            token_program_b,
+            associated_token_program,
+        )?;
+    }
+
+    // Move token A from the old escrow to the new one.
+    let balance = new_vault_a_info.as_token_account()?.amount();
+    let moved = claim_a.min(amount_a);
+    transfer_checked_signed_with_bump(
+        order_info,
+        vault_a_info,
+        mint_a_info,
+        new_vault_a_info,
+        token_program_a,
+        moved,
+        decimals_a,
+        &[ORDER, signer_info.key.as_ref(), &order.id.to_le_bytes()],
+        order.bump,
+    )?;
+    let received = new_vault_a_info.as_token_account()?.amount() - balance;
+
+    // Top up any shortfall, grossed up so the new escrow receives it net of any transfer fee.
+    if received < amount_a {
+        transfer_checked(
+            signer_info,
+            sender_info,
+            mint_a_info,
+            new_vault_a_info,
+            token_program_a,
+            pre_fee_amount(mint_a_info, clock.epoch, amount_a - received)?,
+            decimals_a,
+        )?;
+    }
+
+    // Return any excess to the owner.
+    if claim_a > moved {
+        transfer_checked_signed_with_bump(
+            order_info,
+            vault_a_info,
+            mint_a_info,
+            sender_info,
+            token_program_a,
+            claim_a - moved,
+            decimals_a,
+            &[ORDER, signer_info.key.as_ref(), &order.id.to_le_bytes()],
+            order.bump,
+        )?;
+    }
+
+    // Emit the events.
+    OrderReplaced {
+        order: *order_info.key,
+        new_order: *new_order_info.key,
+        amount_b: claim_b - fee,
+        fee,
+    }
+    .log();
+    OrderOpened {
+        authority: *signer_info.key,
+        order: *new_order_info.key,
+        mint_a: order.mint_a,
+        mint_b: order.mint_b,
+        amount_a,
+        amount_b,
+        expires_at,
+        id,
+    }
+    .log();
+
+    Ok(())
+}
@@ -0,0 +1,84 @@
+use protobook_api::prelude::*;
+use steel::*;
+
+/// Transfers a receipt to a new authority, merging it into their receipt if one exists.
+pub fn process_transfer_receipt(accounts: &[AccountInfo<'_>], _data: &[u8]) -> ProgramResult {
+    // Load accounts.
+    let [signer_info, new_authority_info, new_receipt_info, order_info, receipt_info, system_program] =
+        accounts
+    else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    signer_info.is_signer()?;
+    if new_authority_info.key == signer_info.key {
+        return Err(ProtobookError::SameAuthority.into());
+    }
+    let mut order = OrderV2::load(order_info)?;
+    let mut receipt = ReceiptV2::load(receipt_info)?;
+    receipt
+        .assert_mut_err(
+            |r| r.authority == *signer_info.key,
+            ProtobookError::NotAuthority.into(),
+        )?
+        .assert_mut_err(
+            |r| r.order == *order_info.key,
+            ProtobookError::ReceiptOrderMismatch.into(),
+        )?;
+    new_receipt_info.is_writable()?;
+    system_program.is_program(&system_program::ID)?;
+
+    // Create the new receipt, or merge into it if it already exists.
+    // An existing receipt is checked against its stored bump.
+    if new_receipt_info.data_is_empty() {
+        let (new_receipt_address, new_receipt_bump) =
+            receipt_pda(*new_authority_info.key, *order_info.key);
+        new_receipt_info.has_address(&new_receipt_address)?;
+        create_program_account_with_bump::<ReceiptV2>(
+            new_receipt_info,
+            system_program,
+            signer_info,
+            &protobook_api::ID,
+            &[
+                RECEIPT,
+                new_authority_info.key.as_ref(),
+                order_info.key.as_ref(),
+            ],
+            new_receipt_bump,
+        )?;
+        let new_receipt = new_receipt_info.as_account_mut::<ReceiptV2>(&protobook_api::ID)?;
+        new_receipt.authority = *new_authority_info.key;
+        new_receipt.deposit = 0;
+        new_receipt.order = *order_info.key;
+        new_receipt.tip = 0;
+        new_receipt.bump = new_receipt_bump;
+    } else {
+        order.total_receipts -= 1;
+        order.save(order_info)?;
+    }
+
+    // Move the deposit and tip to the new receipt.
+    let mut new_receipt = ReceiptV2::load(new_receipt_info)?;
+    new_receipt_info.has_address(&receipt_address(
+        *new_authority_info.key,
+        *order_info.key,
+        new_receipt.bump,
+    )?)?;
+    new_receipt.deposit += receipt.deposit;
+    new_receipt.tip += receipt.tip;
+    new_receipt.save(new_receipt_info)?;
+    receipt_info.send(receipt.tip, new_receipt_info);
+
+    // Close the old receipt account.
+    receipt_info.close(signer_info)?;
+
+    // Emit the event.
+    ReceiptTransferred {
+        order: *order_info.key,
+        authority: *signer_info.key,
+        new_authority: *new_authority_info.key,
+        deposit: receipt.deposit,
+    }
+    .log();
+
+    Ok(())
+}
@@ -0,0 +1,45 @@
+use protobook_api::prelude::*;
+use steel::*;
+
+/// Updates the config, including pausing or resuming new orders and fills.
+pub fn process_update_config(accounts: &[AccountInfo<'_>], data: &[u8]) -> ProgramResult {
+    // Parse args.
+    let args = UpdateConfig::try_from_bytes(data)?;
+    let fee = u64::from_le_bytes(args.fee);
+    let is_paused = u64::from_le_bytes(args.is_paused);
+    if fee > MAX_FEE {
+        return Err(ProtobookError::FeeTooHigh.into());
+    }
+    if is_paused > 1 {
+        return Err(ProtobookError::InvalidFlag.into());
+    }
+
+    // Load accounts.
+    let [signer_info, config_info] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    signer_info.is_signer()?;
+    let config = config_info
+        .as_account_mut::<Config>(&protobook_api::ID)?
+        .assert_mut_err(
+            |c| c.admin == *signer_info.key,
+            ProtobookError::NotAuthority.into(),
+        )?;
+
+    // Update the config.
+    config.admin = args.admin;
+    config.fee = fee;
+    config.fee_collector = args.fee_collector;
+    config.is_paused = is_paused;
+
+    // Emit the event.
+    ConfigUpdated {
+        admin: config.admin,
+        fee: config.fee,
+        fee_collector: config.fee_collector,
+        is_paused: config.is_paused,
+    }
+    .log();
+
+    Ok(())
+}